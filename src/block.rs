@@ -3,13 +3,31 @@
 mod block_test;
 
 use derive_more::Display;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 
+use crate::StarknetApiError;
+use crate::block_commitment::{
+    EventCommitment, ReceiptCommitment, StateDiffCommitment, TransactionCommitment,
+    calculate_event_commitment, calculate_receipt_commitment, calculate_transaction_commitment,
+};
 use crate::core::{ContractAddress, GlobalRoot};
-use crate::hash::StarkHash;
+use crate::data_availability::L1DataAvailabilityMode;
+use crate::hash::{HashChain, StarkFelt, StarkHash, ascii_as_felt, lazy_ascii_as_felt};
 use crate::serde_utils::{BytesAsHex, PrefixedBytesAsHex};
 use crate::transaction::{Transaction, TransactionHash, TransactionOutput};
 
+static STARKNET_BLOCK_HASH0: Lazy<StarkFelt> = lazy_ascii_as_felt!("STARKNET_BLOCK_HASH0");
+
+/// From this Starknet version on, all transaction signatures are committed to in the block.
+pub(crate) const STARKNET_VERSION_0_11_1: [u64; 3] = [0, 11, 1];
+/// From this Starknet version on, block hashes and commitments are calculated with the Poseidon
+/// hash.
+pub(crate) const STARKNET_VERSION_0_13_2: [u64; 3] = [0, 13, 2];
+/// From this Starknet version on, block hashes also hash the L2 gas price, by a scheme that is not
+/// supported.
+pub(crate) const STARKNET_VERSION_0_13_4: [u64; 3] = [0, 13, 4];
+
 /// A block.
 #[derive(Debug, Default, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
 pub struct Block {
@@ -17,7 +35,75 @@ pub struct Block {
     pub body: BlockBody,
 }
 
+impl Block {
+    /// Verifies that the hash in the header of the block is the hash of its content: that the
+    /// transaction and event counts and commitments in the header, and from Starknet 0.13.2 the
+    /// receipt commitment, are those of the body, and that the header hashes to the block hash.
+    ///
+    /// The state diff is not part of the block, so its length and commitment are only hashed.
+    pub fn verify_hash(&self) -> Result<(), StarknetApiError> {
+        let calculated = self.header.calculate_hash()?;
+        self.verify_body()?;
+        if calculated != self.header.block_hash {
+            return Err(StarknetApiError::BlockHashMismatch {
+                block_number: self.header.block_number,
+                expected: self.header.block_hash,
+                calculated,
+            });
+        }
+        Ok(())
+    }
+
+    fn verify_body(&self) -> Result<(), StarknetApiError> {
+        let header = &self.header;
+        let version = &header.starknet_version;
+        for (field, length) in [
+            ("transaction_hashes", self.body.transaction_hashes.len()),
+            ("transaction_outputs", self.body.transaction_outputs.len()),
+        ] {
+            if length != self.body.transactions.len() {
+                return Err(StarknetApiError::BlockBodyLengthMismatch {
+                    block_number: header.block_number,
+                    field,
+                    length,
+                    transaction_count: self.body.transactions.len(),
+                });
+            }
+        }
+        let event_count =
+            self.body.transaction_outputs.iter().map(|output| output.events().len()).sum();
+        header.verify_count(
+            "transaction_count",
+            header.transaction_count,
+            self.body.transactions.len(),
+        )?;
+        header.verify_count("event_count", header.event_count, event_count)?;
+
+        header.verify_commitment(
+            "transaction_commitment",
+            header.transaction_commitment.map(|commitment| commitment.0),
+            calculate_transaction_commitment(&self.body, version).0,
+        )?;
+        header.verify_commitment(
+            "event_commitment",
+            header.event_commitment.map(|commitment| commitment.0),
+            calculate_event_commitment(&self.body, version).0,
+        )?;
+        if version.is_at_least(&STARKNET_VERSION_0_13_2) {
+            header.verify_commitment(
+                "receipt_commitment",
+                header.receipt_commitment.map(|commitment| commitment.0),
+                calculate_receipt_commitment(&self.body).0,
+            )?;
+        }
+        Ok(())
+    }
+}
+
 /// The header of a [Block](`crate::block::Block`).
+///
+/// Fields that were added to the protocol over time default to their empty values when
+/// deserializing the headers of older blocks.
 #[derive(Debug, Default, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
 pub struct BlockHeader {
     // TODO: Consider removing the block hash from the header (note it can be computed from
@@ -25,11 +111,188 @@ pub struct BlockHeader {
     pub block_hash: BlockHash,
     pub parent_hash: BlockHash,
     pub block_number: BlockNumber,
-    pub gas_price: GasPrice,
+    #[serde(alias = "gas_price")]
+    pub l1_gas_price: GasPricePerToken,
+    #[serde(default)]
+    pub l1_data_gas_price: GasPricePerToken,
     pub state_root: GlobalRoot,
     pub sequencer: ContractAddress,
     pub timestamp: BlockTimestamp,
-    // TODO: add missing commitments.
+    #[serde(default)]
+    pub l1_da_mode: L1DataAvailabilityMode,
+    #[serde(default)]
+    pub starknet_version: StarknetVersion,
+    #[serde(default)]
+    pub transaction_count: usize,
+    #[serde(default)]
+    pub event_count: usize,
+    #[serde(default)]
+    pub state_diff_length: usize,
+    #[serde(default)]
+    pub transaction_commitment: Option<TransactionCommitment>,
+    #[serde(default)]
+    pub event_commitment: Option<EventCommitment>,
+    #[serde(default)]
+    pub receipt_commitment: Option<ReceiptCommitment>,
+    #[serde(default)]
+    pub state_diff_commitment: Option<StateDiffCommitment>,
+}
+
+impl BlockHeader {
+    /// Calculates the hash of the block, by the scheme of its Starknet version: the Pedersen hash
+    /// before Starknet 0.13.2 and the Poseidon hash from it on. Returns an error if a commitment
+    /// that the scheme hashes is missing, or for blocks of Starknet 0.13.4 and later, whose scheme
+    /// is not supported.
+    pub fn calculate_hash(&self) -> Result<BlockHash, StarknetApiError> {
+        if self.starknet_version.is_at_least(&STARKNET_VERSION_0_13_4) {
+            return Err(StarknetApiError::UnsupportedBlockHashVersion {
+                block_number: self.block_number,
+                starknet_version: self.starknet_version.clone(),
+            });
+        }
+        if self.starknet_version.is_at_least(&STARKNET_VERSION_0_13_2) {
+            self.calculate_poseidon_hash()
+        } else {
+            self.calculate_pedersen_hash()
+        }
+    }
+
+    fn calculate_pedersen_hash(&self) -> Result<BlockHash, StarknetApiError> {
+        Ok(BlockHash(
+            HashChain::new()
+                .chain(&self.block_number.0.into())
+                .chain(&self.state_root.0)
+                .chain(self.sequencer.0.key())
+                .chain(&self.timestamp.0.into())
+                .chain(&self.transaction_count.into())
+                .chain(&self.required_commitment(
+                    "transaction_commitment",
+                    self.transaction_commitment.map(|commitment| commitment.0),
+                )?)
+                .chain(&self.event_count.into())
+                .chain(&self.required_commitment(
+                    "event_commitment",
+                    self.event_commitment.map(|commitment| commitment.0),
+                )?)
+                // Reserved for the protocol version.
+                .chain(&StarkFelt::ZERO)
+                // Reserved for extra data.
+                .chain(&StarkFelt::ZERO)
+                .chain(&self.parent_hash.0)
+                .get_pedersen_hash(),
+        ))
+    }
+
+    fn calculate_poseidon_hash(&self) -> Result<BlockHash, StarknetApiError> {
+        Ok(BlockHash(
+            HashChain::new()
+                .chain(&STARKNET_BLOCK_HASH0)
+                .chain(&self.block_number.0.into())
+                .chain(&self.state_root.0)
+                .chain(self.sequencer.0.key())
+                .chain(&self.timestamp.0.into())
+                .chain(&concat_counts(
+                    self.transaction_count,
+                    self.event_count,
+                    self.state_diff_length,
+                    self.l1_da_mode,
+                ))
+                .chain(&self.required_commitment(
+                    "state_diff_commitment",
+                    self.state_diff_commitment.map(|commitment| commitment.0),
+                )?)
+                .chain(&self.required_commitment(
+                    "transaction_commitment",
+                    self.transaction_commitment.map(|commitment| commitment.0),
+                )?)
+                .chain(&self.required_commitment(
+                    "event_commitment",
+                    self.event_commitment.map(|commitment| commitment.0),
+                )?)
+                .chain(&self.required_commitment(
+                    "receipt_commitment",
+                    self.receipt_commitment.map(|commitment| commitment.0),
+                )?)
+                .chain(&self.l1_gas_price.price_in_wei.0.into())
+                .chain(&self.l1_gas_price.price_in_fri.0.into())
+                .chain(&self.l1_data_gas_price.price_in_wei.0.into())
+                .chain(&self.l1_data_gas_price.price_in_fri.0.into())
+                .chain(&ascii_as_felt(&self.starknet_version.0)?)
+                .chain(&StarkFelt::ZERO)
+                .chain(&self.parent_hash.0)
+                .get_poseidon_hash(),
+        ))
+    }
+
+    fn required_commitment(
+        &self,
+        commitment: &'static str,
+        value: Option<StarkHash>,
+    ) -> Result<StarkHash, StarknetApiError> {
+        value.ok_or(StarknetApiError::MissingBlockCommitment {
+            block_number: self.block_number,
+            commitment,
+        })
+    }
+
+    fn verify_commitment(
+        &self,
+        commitment: &'static str,
+        expected: Option<StarkHash>,
+        calculated: StarkHash,
+    ) -> Result<(), StarknetApiError> {
+        let expected = self.required_commitment(commitment, expected)?;
+        if expected != calculated {
+            return Err(StarknetApiError::BlockCommitmentMismatch {
+                block_number: self.block_number,
+                commitment,
+                expected,
+                calculated,
+            });
+        }
+        Ok(())
+    }
+
+    fn verify_count(
+        &self,
+        count: &'static str,
+        expected: usize,
+        calculated: usize,
+    ) -> Result<(), StarknetApiError> {
+        if expected != calculated {
+            return Err(StarknetApiError::BlockCountMismatch {
+                block_number: self.block_number,
+                count,
+                expected,
+                calculated,
+            });
+        }
+        Ok(())
+    }
+}
+
+// Returns [transaction_count (64 bits) | event_count (64 bits) | state_diff_length (64 bits) |
+// l1_da_mode (1 bit: 0 for calldata, 1 for blob) | 0 (63 bits)].
+fn concat_counts(
+    transaction_count: usize,
+    event_count: usize,
+    state_diff_length: usize,
+    l1_da_mode: L1DataAvailabilityMode,
+) -> StarkFelt {
+    let l1_da_mode_byte = match l1_da_mode {
+        L1DataAvailabilityMode::Calldata => 0,
+        L1DataAvailabilityMode::Blob => 0b1000_0000,
+    };
+    let mut bytes = [0u8; 32];
+    bytes[0..8].copy_from_slice(&to_u64(transaction_count).to_be_bytes());
+    bytes[8..16].copy_from_slice(&to_u64(event_count).to_be_bytes());
+    bytes[16..24].copy_from_slice(&to_u64(state_diff_length).to_be_bytes());
+    bytes[24] = l1_da_mode_byte;
+    StarkFelt::new(bytes).expect("Block counts should be smaller than 2^59.")
+}
+
+fn to_u64(count: usize) -> u64 {
+    u64::try_from(count).expect("Expect a count that fits in 64 bits.")
 }
 
 /// The [transactions](`crate::transaction::Transaction`) and their
@@ -129,6 +392,55 @@ impl From<GasPrice> for PrefixedBytesAsHex<16_usize> {
     }
 }
 
+/// The L1 gas price at a [Block](`crate::block::Block`), in both of the tokens fees are paid in.
+#[derive(
+    Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord,
+)]
+#[serde(from = "DeserializableGasPricePerToken")]
+pub struct GasPricePerToken {
+    pub price_in_wei: GasPrice,
+    pub price_in_fri: GasPrice,
+}
+
+// Blocks from before fees could be paid in fri have a single gas price, in wei.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DeserializableGasPricePerToken {
+    PerToken { price_in_wei: GasPrice, price_in_fri: GasPrice },
+    InWei(GasPrice),
+}
+
+impl From<DeserializableGasPricePerToken> for GasPricePerToken {
+    fn from(price: DeserializableGasPricePerToken) -> Self {
+        match price {
+            DeserializableGasPricePerToken::PerToken { price_in_wei, price_in_fri } => {
+                GasPricePerToken { price_in_wei, price_in_fri }
+            }
+            DeserializableGasPricePerToken::InWei(price_in_wei) => {
+                GasPricePerToken { price_in_wei, price_in_fri: GasPrice::default() }
+            }
+        }
+    }
+}
+
+/// The version of Starknet that created a [Block](`crate::block::Block`), e.g. "0.13.2".
+#[derive(
+    Debug, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord, Display,
+)]
+pub struct StarknetVersion(pub String);
+
+impl StarknetVersion {
+    // Returns whether the version is at least the given one. Versions that are missing or cannot be
+    // parsed predate all others.
+    pub(crate) fn is_at_least(&self, other: &[u64]) -> bool {
+        let parsed: Result<Vec<u64>, _> = self.0.split('.').map(|part| part.parse()).collect();
+        match parsed {
+            Ok(version) => version.as_slice() >= other,
+            Err(_) => false,
+        }
+    }
+}
+
 /// The timestamp of a [Block](`crate::block::Block`).
 #[derive(
     Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord,
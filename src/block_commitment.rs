@@ -0,0 +1,225 @@
+#[cfg(test)]
+#[path = "block_commitment_test.rs"]
+mod block_commitment_test;
+
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+use crate::block::{BlockBody, STARKNET_VERSION_0_11_1, STARKNET_VERSION_0_13_2, StarknetVersion};
+use crate::hash::{
+    HashChain, Pedersen, Poseidon, StarkFelt, StarkHash, StarkHasher, pedersen_hash,
+    pedersen_hash_array, starknet_keccak,
+};
+use crate::patricia::calculate_root;
+use crate::transaction::{
+    Event, MessageToL1, Transaction, TransactionExecutionStatus, TransactionHash,
+    TransactionOutput, TransactionSignature,
+};
+
+/// The root of the Patricia-Merkle tree of the transactions of a [Block](`crate::block::Block`).
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    Display,
+    Eq,
+    PartialEq,
+    Hash,
+    Deserialize,
+    Serialize,
+    PartialOrd,
+    Ord,
+)]
+pub struct TransactionCommitment(pub StarkHash);
+
+/// The root of the Patricia-Merkle tree of the events of a [Block](`crate::block::Block`).
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    Display,
+    Eq,
+    PartialEq,
+    Hash,
+    Deserialize,
+    Serialize,
+    PartialOrd,
+    Ord,
+)]
+pub struct EventCommitment(pub StarkHash);
+
+/// The root of the Patricia-Merkle tree of the transaction receipts of a
+/// [Block](`crate::block::Block`).
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    Display,
+    Eq,
+    PartialEq,
+    Hash,
+    Deserialize,
+    Serialize,
+    PartialOrd,
+    Ord,
+)]
+pub struct ReceiptCommitment(pub StarkHash);
+
+/// The commitment to the state diff of a [Block](`crate::block::Block`).
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    Display,
+    Eq,
+    PartialEq,
+    Hash,
+    Deserialize,
+    Serialize,
+    PartialOrd,
+    Ord,
+)]
+pub struct StateDiffCommitment(pub StarkHash);
+
+/// Calculates the commitment to the transactions of a block and their signatures, by the scheme
+/// of the given Starknet version.
+pub fn calculate_transaction_commitment(
+    body: &BlockBody,
+    starknet_version: &StarknetVersion,
+) -> TransactionCommitment {
+    let transactions = body.transactions.iter().zip(body.transaction_hashes.iter());
+    if starknet_version.is_at_least(&STARKNET_VERSION_0_13_2) {
+        let leaves: Vec<StarkFelt> = transactions
+            .map(|(tx, tx_hash)| calculate_transaction_leaf(tx_hash, &tx.signature()))
+            .collect();
+        return TransactionCommitment(calculate_root::<Poseidon>(&leaves));
+    }
+
+    let all_signatures_committed = starknet_version.is_at_least(&STARKNET_VERSION_0_11_1);
+    let leaves: Vec<StarkFelt> = transactions
+        .map(|(tx, tx_hash)| {
+            let signature = match tx {
+                Transaction::Invoke(_) => tx.signature(),
+                _ if all_signatures_committed => tx.signature(),
+                _ => TransactionSignature::default(),
+            };
+            pedersen_hash(&tx_hash.0, &pedersen_hash_array(&signature.0))
+        })
+        .collect();
+    TransactionCommitment(calculate_root::<Pedersen>(&leaves))
+}
+
+/// Calculates the commitment to the events emitted in a block, by the scheme of the given Starknet
+/// version.
+pub fn calculate_event_commitment(
+    body: &BlockBody,
+    starknet_version: &StarknetVersion,
+) -> EventCommitment {
+    let events =
+        body.transaction_outputs.iter().zip(body.transaction_hashes.iter()).flat_map(
+            |(output, tx_hash)| output.events().iter().map(move |event| (event, tx_hash)),
+        );
+    if starknet_version.is_at_least(&STARKNET_VERSION_0_13_2) {
+        let leaves: Vec<StarkFelt> =
+            events.map(|(event, tx_hash)| calculate_event_leaf(event, tx_hash)).collect();
+        return EventCommitment(calculate_root::<Poseidon>(&leaves));
+    }
+
+    let leaves: Vec<StarkFelt> = events
+        .map(|(event, _)| {
+            let keys: Vec<StarkFelt> = event.content.keys.iter().map(|key| key.0).collect();
+            pedersen_hash_array(&[
+                *event.from_address.0.key(),
+                pedersen_hash_array(&keys),
+                pedersen_hash_array(&event.content.data.0),
+            ])
+        })
+        .collect();
+    EventCommitment(calculate_root::<Pedersen>(&leaves))
+}
+
+/// Calculates the commitment to the transaction receipts of a block, as introduced in Starknet
+/// 0.13.2.
+pub fn calculate_receipt_commitment(body: &BlockBody) -> ReceiptCommitment {
+    calculate_receipt_commitment_with_hasher::<Poseidon>(body)
+}
+
+/// Calculates the commitment to the transaction receipts of a block as in
+/// [`calculate_receipt_commitment`], using `H` instead of the Poseidon hash.
+pub fn calculate_receipt_commitment_with_hasher<H: StarkHasher>(
+    body: &BlockBody,
+) -> ReceiptCommitment {
+    let leaves: Vec<StarkFelt> = body
+        .transaction_outputs
+        .iter()
+        .zip(body.transaction_hashes.iter())
+        .map(|(output, tx_hash)| calculate_receipt_leaf::<H>(output, tx_hash))
+        .collect();
+    ReceiptCommitment(calculate_root::<H>(&leaves))
+}
+
+// Poseidon(transaction_hash, signature), where an empty signature is hashed as [0].
+fn calculate_transaction_leaf(
+    transaction_hash: &TransactionHash,
+    signature: &TransactionSignature,
+) -> StarkFelt {
+    let signature: &[StarkFelt] =
+        if signature.0.is_empty() { &[StarkFelt::ZERO] } else { &signature.0 };
+    HashChain::new().chain(&transaction_hash.0).chain_iter(signature.iter()).get_poseidon_hash()
+}
+
+// Poseidon(from_address, transaction_hash, num_keys, keys, num_data, data).
+fn calculate_event_leaf(event: &Event, transaction_hash: &TransactionHash) -> StarkFelt {
+    let keys: Vec<StarkFelt> = event.content.keys.iter().map(|key| key.0).collect();
+    HashChain::new()
+        .chain(event.from_address.0.key())
+        .chain(&transaction_hash.0)
+        .chain_size_and_elements(&keys)
+        .chain_size_and_elements(&event.content.data.0)
+        .get_poseidon_hash()
+}
+
+// H(transaction_hash, actual_fee, messages_sent_hash, revert_reason_hash, l2_gas_consumed,
+// l1_gas_consumed, l1_data_gas_consumed).
+fn calculate_receipt_leaf<H: StarkHasher>(
+    output: &TransactionOutput,
+    transaction_hash: &TransactionHash,
+) -> StarkFelt {
+    let gas_consumed = output.gas_consumed();
+    HashChain::new()
+        .chain(&transaction_hash.0)
+        .chain(&output.actual_fee().into())
+        .chain(&calculate_messages_sent_hash::<H>(output.messages_sent()))
+        .chain(&calculate_revert_reason_hash(output.execution_status()))
+        // The L2 gas consumed is not reported yet.
+        .chain(&StarkFelt::ZERO)
+        .chain(&gas_consumed.l1_gas.into())
+        .chain(&gas_consumed.l1_data_gas.into())
+        .get_hash::<H>()
+}
+
+// H(num_messages, from_address, to_address, payload_length, payload, ...).
+fn calculate_messages_sent_hash<H: StarkHasher>(messages_sent: &[MessageToL1]) -> StarkFelt {
+    let mut messages_hash = HashChain::new().chain(&messages_sent.len().into());
+    for message in messages_sent {
+        messages_hash = messages_hash
+            .chain(message.from_address.0.key())
+            .chain(&message.to_address.into())
+            .chain_size_and_elements(&message.payload.0);
+    }
+    messages_hash.get_hash::<H>()
+}
+
+// Zero for a successful transaction and the Starknet Keccak of the revert reason otherwise.
+fn calculate_revert_reason_hash(execution_status: &TransactionExecutionStatus) -> StarkFelt {
+    match execution_status {
+        TransactionExecutionStatus::Succeeded => StarkFelt::ZERO,
+        TransactionExecutionStatus::Reverted(status) => {
+            starknet_keccak(status.revert_reason.as_bytes())
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use crate::block::{BlockBody, StarknetVersion};
+use crate::block_commitment::{
+    EventCommitment, ReceiptCommitment, TransactionCommitment, calculate_event_commitment,
+    calculate_receipt_commitment, calculate_receipt_commitment_with_hasher,
+    calculate_transaction_commitment,
+};
+use crate::core::{ContractAddress, EthAddress, PatriciaKey};
+use crate::hash::{
+    Pedersen, Poseidon, StarkFelt, StarkHash, StarkHasher, pedersen_hash, pedersen_hash_array,
+    poseidon_hash_many, starknet_keccak,
+};
+use crate::patricia::calculate_root;
+use crate::transaction::{
+    DeclareTransaction, DeclareTransactionOutput, DeclareTransactionV0V1, Event, EventContent,
+    EventData, EventKey, Fee, GasVector, InvokeTransaction, InvokeTransactionOutput,
+    InvokeTransactionV1, L2ToL1Payload, MessageToL1, RevertedTransactionExecutionStatus,
+    Transaction, TransactionExecutionStatus, TransactionHash, TransactionOutput,
+    TransactionSignature,
+};
+use crate::{patricia_key, stark_felt};
+
+fn get_block_body() -> BlockBody {
+    let invoke = Transaction::Invoke(InvokeTransaction::V1(InvokeTransactionV1 {
+        signature: TransactionSignature(vec![stark_felt!("0x1"), stark_felt!("0x2")]),
+        ..InvokeTransactionV1::default()
+    }));
+    let declare = Transaction::Declare(DeclareTransaction::V1(DeclareTransactionV0V1 {
+        signature: TransactionSignature(vec![stark_felt!("0x3")]),
+        ..DeclareTransactionV0V1::default()
+    }));
+    let event = Event {
+        from_address: ContractAddress(patricia_key!("0x4")),
+        content: EventContent {
+            keys: vec![EventKey(stark_felt!("0x5"))],
+            data: EventData(vec![stark_felt!("0x6"), stark_felt!("0x7")]),
+        },
+    };
+    let invoke_output = TransactionOutput::Invoke(InvokeTransactionOutput {
+        actual_fee: Fee(8),
+        messages_sent: vec![MessageToL1 {
+            from_address: ContractAddress(patricia_key!("0x9")),
+            to_address: EthAddress::try_from(stark_felt!("0xa")).unwrap(),
+            payload: L2ToL1Payload(vec![stark_felt!("0xb")]),
+        }],
+        events: vec![event],
+        gas_consumed: GasVector { l1_gas: 12, l1_data_gas: 13 },
+        ..InvokeTransactionOutput::default()
+    });
+    let declare_output = TransactionOutput::Declare(DeclareTransactionOutput {
+        actual_fee: Fee(14),
+        execution_status: TransactionExecutionStatus::Reverted(
+            RevertedTransactionExecutionStatus { revert_reason: "reason".to_owned() },
+        ),
+        ..DeclareTransactionOutput::default()
+    });
+    BlockBody {
+        transactions: vec![invoke, declare],
+        transaction_outputs: vec![invoke_output, declare_output],
+        transaction_hashes: vec![
+            TransactionHash(stark_felt!("0x100")),
+            TransactionHash(stark_felt!("0x200")),
+        ],
+    }
+}
+
+fn version(version: &str) -> StarknetVersion {
+    StarknetVersion(version.to_owned())
+}
+
+#[test]
+fn transaction_commitment() {
+    let body = get_block_body();
+
+    let expected_root = calculate_root::<Poseidon>(&[
+        poseidon_hash_many(&[stark_felt!("0x100"), stark_felt!("0x1"), stark_felt!("0x2")]),
+        poseidon_hash_many(&[stark_felt!("0x200"), stark_felt!("0x3")]),
+    ]);
+    assert_eq!(
+        calculate_transaction_commitment(&body, &version("0.13.2")),
+        TransactionCommitment(expected_root)
+    );
+
+    let invoke_leaf = pedersen_hash(
+        &stark_felt!("0x100"),
+        &pedersen_hash_array(&[stark_felt!("0x1"), stark_felt!("0x2")]),
+    );
+    let expected_root = calculate_root::<Pedersen>(&[
+        invoke_leaf,
+        pedersen_hash(&stark_felt!("0x200"), &pedersen_hash_array(&[stark_felt!("0x3")])),
+    ]);
+    assert_eq!(
+        calculate_transaction_commitment(&body, &version("0.11.1")),
+        TransactionCommitment(expected_root)
+    );
+
+    // Before Starknet 0.11.1, only the signatures of invoke transactions were committed to.
+    let expected_root = calculate_root::<Pedersen>(&[
+        invoke_leaf,
+        pedersen_hash(&stark_felt!("0x200"), &pedersen_hash_array(&[])),
+    ]);
+    assert_eq!(
+        calculate_transaction_commitment(&body, &version("0.11.0")),
+        TransactionCommitment(expected_root)
+    );
+}
+
+#[test]
+fn transaction_commitment_with_empty_signature() {
+    let mut body = get_block_body();
+    body.transactions[0] =
+        Transaction::Invoke(InvokeTransaction::V1(InvokeTransactionV1::default()));
+    body.transactions.truncate(1);
+    body.transaction_hashes.truncate(1);
+
+    let expected_root =
+        calculate_root::<Poseidon>(&[poseidon_hash_many(&[stark_felt!("0x100"), StarkFelt::ZERO])]);
+    assert_eq!(
+        calculate_transaction_commitment(&body, &version("0.13.2")),
+        TransactionCommitment(expected_root)
+    );
+}
+
+#[test]
+fn event_commitment() {
+    let body = get_block_body();
+
+    let expected_root = calculate_root::<Poseidon>(&[poseidon_hash_many(&[
+        stark_felt!("0x4"),
+        stark_felt!("0x100"),
+        stark_felt!(1_u64),
+        stark_felt!("0x5"),
+        stark_felt!(2_u64),
+        stark_felt!("0x6"),
+        stark_felt!("0x7"),
+    ])]);
+    assert_eq!(
+        calculate_event_commitment(&body, &version("0.13.2")),
+        EventCommitment(expected_root)
+    );
+
+    let expected_root = calculate_root::<Pedersen>(&[pedersen_hash_array(&[
+        stark_felt!("0x4"),
+        pedersen_hash_array(&[stark_felt!("0x5")]),
+        pedersen_hash_array(&[stark_felt!("0x6"), stark_felt!("0x7")]),
+    ])]);
+    assert_eq!(
+        calculate_event_commitment(&body, &version("0.13.1")),
+        EventCommitment(expected_root)
+    );
+}
+
+// The receipt commitment of the block body, by the scheme of Starknet 0.13.2 with H instead of the
+// Poseidon hash.
+fn get_receipt_commitment<H: StarkHasher>() -> ReceiptCommitment {
+    let messages_hash = H::hash_array(&[
+        stark_felt!(1_u64),
+        stark_felt!("0x9"),
+        stark_felt!("0xa"),
+        stark_felt!(1_u64),
+        stark_felt!("0xb"),
+    ]);
+    let invoke_leaf = H::hash_array(&[
+        stark_felt!("0x100"),
+        stark_felt!(8_u64),
+        messages_hash,
+        StarkFelt::ZERO,
+        StarkFelt::ZERO,
+        stark_felt!(12_u64),
+        stark_felt!(13_u64),
+    ]);
+    let declare_leaf = H::hash_array(&[
+        stark_felt!("0x200"),
+        stark_felt!(14_u64),
+        H::hash_array(&[StarkFelt::ZERO]),
+        starknet_keccak(b"reason"),
+        StarkFelt::ZERO,
+        StarkFelt::ZERO,
+        StarkFelt::ZERO,
+    ]);
+    ReceiptCommitment(calculate_root::<H>(&[invoke_leaf, declare_leaf]))
+}
+
+#[test]
+fn receipt_commitment() {
+    let body = get_block_body();
+    assert_eq!(calculate_receipt_commitment(&body), get_receipt_commitment::<Poseidon>());
+    assert_eq!(
+        calculate_receipt_commitment_with_hasher::<Pedersen>(&body),
+        get_receipt_commitment::<Pedersen>()
+    );
+}
@@ -1,4 +1,21 @@
-use crate::block::BlockNumber;
+use assert_matches::assert_matches;
+
+use crate::block::{
+    Block, BlockBody, BlockHash, BlockHeader, BlockNumber, BlockTimestamp, GasPrice,
+    GasPricePerToken, StarknetVersion, concat_counts,
+};
+use crate::block_commitment::{
+    EventCommitment, ReceiptCommitment, StateDiffCommitment, TransactionCommitment,
+    calculate_event_commitment, calculate_receipt_commitment, calculate_transaction_commitment,
+};
+use crate::core::{ContractAddress, GlobalRoot, PatriciaKey};
+use crate::data_availability::L1DataAvailabilityMode;
+use crate::hash::{StarkFelt, StarkHash, ascii_as_felt, pedersen_hash_array, poseidon_hash_many};
+use crate::transaction::{
+    Event, EventContent, EventData, EventKey, L1HandlerTransaction, L1HandlerTransactionOutput,
+    Transaction, TransactionHash, TransactionOutput,
+};
+use crate::{StarknetApiError, patricia_key, stark_felt};
 
 #[test]
 fn test_block_number_iteration() {
@@ -20,3 +37,276 @@ fn test_block_number_iteration() {
 
     assert_eq!(expected, from_iter);
 }
+
+#[test]
+fn test_concat_counts() {
+    let concated = concat_counts(4, 3, 2, L1DataAvailabilityMode::Blob);
+    let expected_felt =
+        StarkFelt::try_from("0x0000000000000004000000000000000300000000000000028000000000000000")
+            .unwrap();
+    assert_eq!(concated, expected_felt);
+
+    let concated = concat_counts(4, 3, 2, L1DataAvailabilityMode::Calldata);
+    let expected_felt =
+        StarkFelt::try_from("0x0000000000000004000000000000000300000000000000020000000000000000")
+            .unwrap();
+    assert_eq!(concated, expected_felt);
+}
+
+#[test]
+fn test_block_hash_scheme_by_version() {
+    let mut header = BlockHeader {
+        parent_hash: BlockHash(stark_felt!("0x1")),
+        block_number: BlockNumber(2),
+        l1_gas_price: GasPricePerToken { price_in_wei: GasPrice(3), price_in_fri: GasPrice(14) },
+        l1_data_gas_price: GasPricePerToken {
+            price_in_wei: GasPrice(15),
+            price_in_fri: GasPrice(16),
+        },
+        state_root: GlobalRoot(stark_felt!("0x4")),
+        sequencer: ContractAddress(patricia_key!("0x5")),
+        timestamp: BlockTimestamp(6),
+        l1_da_mode: L1DataAvailabilityMode::Blob,
+        starknet_version: StarknetVersion("0.13.1".to_owned()),
+        transaction_count: 7,
+        event_count: 9,
+        state_diff_length: 11,
+        transaction_commitment: Some(TransactionCommitment(stark_felt!("0x8"))),
+        event_commitment: Some(EventCommitment(stark_felt!("0xa"))),
+        receipt_commitment: Some(ReceiptCommitment(stark_felt!("0xd"))),
+        state_diff_commitment: Some(StateDiffCommitment(stark_felt!("0xc"))),
+        ..BlockHeader::default()
+    };
+
+    let expected_pedersen_hash = pedersen_hash_array(&[
+        stark_felt!(2_u64),
+        stark_felt!("0x4"),
+        stark_felt!("0x5"),
+        stark_felt!(6_u64),
+        stark_felt!(7_u64),
+        stark_felt!("0x8"),
+        stark_felt!(9_u64),
+        stark_felt!("0xa"),
+        StarkFelt::ZERO,
+        StarkFelt::ZERO,
+        stark_felt!("0x1"),
+    ]);
+    assert_eq!(header.calculate_hash().unwrap(), BlockHash(expected_pedersen_hash));
+
+    header.starknet_version = StarknetVersion("0.13.2".to_owned());
+    let expected_poseidon_hash = poseidon_hash_many(&[
+        ascii_as_felt("STARKNET_BLOCK_HASH0").unwrap(),
+        stark_felt!(2_u64),
+        stark_felt!("0x4"),
+        stark_felt!("0x5"),
+        stark_felt!(6_u64),
+        concat_counts(7, 9, 11, L1DataAvailabilityMode::Blob),
+        stark_felt!("0xc"),
+        stark_felt!("0x8"),
+        stark_felt!("0xa"),
+        stark_felt!("0xd"),
+        stark_felt!(3_u64),
+        stark_felt!(14_u64),
+        stark_felt!(15_u64),
+        stark_felt!(16_u64),
+        ascii_as_felt("0.13.2").unwrap(),
+        StarkFelt::ZERO,
+        stark_felt!("0x1"),
+    ]);
+    assert_eq!(header.calculate_hash().unwrap(), BlockHash(expected_poseidon_hash));
+}
+
+fn get_block(starknet_version: &str) -> Block {
+    let event = Event {
+        from_address: ContractAddress(patricia_key!("0x3")),
+        content: EventContent { keys: vec![EventKey(stark_felt!("0x4"))], data: EventData(vec![]) },
+    };
+    let body = BlockBody {
+        transactions: vec![Transaction::L1Handler(L1HandlerTransaction::default())],
+        transaction_outputs: vec![TransactionOutput::L1Handler(L1HandlerTransactionOutput {
+            events: vec![event],
+            ..L1HandlerTransactionOutput::default()
+        })],
+        transaction_hashes: vec![TransactionHash(stark_felt!("0x2"))],
+    };
+    let starknet_version = StarknetVersion(starknet_version.to_owned());
+    let mut header = BlockHeader {
+        block_number: BlockNumber(1),
+        transaction_count: 1,
+        event_count: 1,
+        transaction_commitment: Some(calculate_transaction_commitment(&body, &starknet_version)),
+        event_commitment: Some(calculate_event_commitment(&body, &starknet_version)),
+        receipt_commitment: Some(calculate_receipt_commitment(&body)),
+        state_diff_commitment: Some(StateDiffCommitment(stark_felt!("0x5"))),
+        starknet_version,
+        ..BlockHeader::default()
+    };
+    header.block_hash = header.calculate_hash().unwrap();
+    Block { header, body }
+}
+
+#[test]
+fn test_verify_block_hash() {
+    for version in ["0.13.1", "0.13.2", "0.13.3"] {
+        get_block(version).verify_hash().unwrap();
+    }
+
+    let mut block = get_block("0.13.2");
+    let calculated = block.header.block_hash;
+    block.header.block_hash = BlockHash(stark_felt!("0x1"));
+    assert_matches!(
+        block.verify_hash(),
+        Err(StarknetApiError::BlockHashMismatch { block_number: BlockNumber(1), expected, calculated: actual })
+        if expected == BlockHash(stark_felt!("0x1")) && actual == calculated
+    );
+}
+
+#[test]
+fn test_verify_block_body() {
+    let mut block = get_block("0.13.2");
+    block.header.transaction_count = 2;
+    assert_matches!(
+        block.verify_hash(),
+        Err(StarknetApiError::BlockCountMismatch {
+            count: "transaction_count",
+            expected: 2,
+            calculated: 1,
+            ..
+        })
+    );
+
+    let mut block = get_block("0.13.2");
+    block.body.transaction_outputs[0] =
+        TransactionOutput::L1Handler(L1HandlerTransactionOutput::default());
+    assert_matches!(
+        block.verify_hash(),
+        Err(StarknetApiError::BlockCountMismatch {
+            count: "event_count",
+            expected: 1,
+            calculated: 0,
+            ..
+        })
+    );
+
+    // The hash is recalculated, so only the commitment to the body is wrong.
+    let mut block = get_block("0.13.2");
+    block.header.event_commitment = Some(EventCommitment(stark_felt!("0x6")));
+    block.header.block_hash = block.header.calculate_hash().unwrap();
+    assert_matches!(
+        block.verify_hash(),
+        Err(StarknetApiError::BlockCommitmentMismatch {
+            commitment: "event_commitment", expected, ..
+        }) if expected == stark_felt!("0x6")
+    );
+
+    let mut block = get_block("0.13.2");
+    block.body.transaction_hashes[0] = TransactionHash(stark_felt!("0x7"));
+    assert_matches!(
+        block.verify_hash(),
+        Err(StarknetApiError::BlockCommitmentMismatch { commitment: "transaction_commitment", .. })
+    );
+}
+
+#[test]
+fn test_verify_block_body_lengths() {
+    let mut block = get_block("0.13.2");
+    block.body.transaction_hashes.push(TransactionHash(stark_felt!("0x3")));
+    assert_matches!(
+        block.verify_hash(),
+        Err(StarknetApiError::BlockBodyLengthMismatch {
+            field: "transaction_hashes",
+            length: 2,
+            transaction_count: 1,
+            ..
+        })
+    );
+
+    let mut block = get_block("0.13.2");
+    block.body.transaction_outputs.clear();
+    assert_matches!(
+        block.verify_hash(),
+        Err(StarknetApiError::BlockBodyLengthMismatch {
+            field: "transaction_outputs",
+            length: 0,
+            transaction_count: 1,
+            ..
+        })
+    );
+}
+
+#[test]
+fn test_unsupported_block_hash_version() {
+    let mut block = get_block("0.13.3");
+    for version in ["0.13.4", "0.14.0"] {
+        block.header.starknet_version = StarknetVersion(version.to_owned());
+        assert_matches!(
+            block.verify_hash(),
+            Err(StarknetApiError::UnsupportedBlockHashVersion {
+                block_number: BlockNumber(1),
+                starknet_version,
+            }) if starknet_version.0 == version
+        );
+    }
+}
+
+#[test]
+fn test_missing_block_commitments() {
+    // Receipts are committed to from Starknet 0.13.2.
+    let mut block = get_block("0.13.1");
+    block.header.receipt_commitment = None;
+    block.verify_hash().unwrap();
+
+    let mut block = get_block("0.13.2");
+    block.header.receipt_commitment = None;
+    assert_matches!(
+        block.verify_hash(),
+        Err(StarknetApiError::MissingBlockCommitment {
+            block_number: BlockNumber(1),
+            commitment: "receipt_commitment",
+        })
+    );
+
+    let mut block = get_block("0.13.2");
+    block.header.state_diff_commitment = None;
+    assert_matches!(
+        block.header.calculate_hash(),
+        Err(StarknetApiError::MissingBlockCommitment { commitment: "state_diff_commitment", .. })
+    );
+
+    let mut block = get_block("0.13.1");
+    block.header.transaction_commitment = None;
+    assert_matches!(
+        block.header.calculate_hash(),
+        Err(StarknetApiError::MissingBlockCommitment { commitment: "transaction_commitment", .. })
+    );
+}
+
+#[test]
+fn test_deserialize_legacy_block_header() {
+    let header = serde_json::json!({
+        "block_hash": "0x1",
+        "parent_hash": "0x2",
+        "block_number": 3,
+        "gas_price": "0x4",
+        "state_root": "0x5",
+        "sequencer": "0x6",
+        "timestamp": 7,
+    });
+    let header: BlockHeader = serde_json::from_value(header).unwrap();
+    assert_eq!(
+        header,
+        BlockHeader {
+            block_hash: BlockHash(stark_felt!("0x1")),
+            parent_hash: BlockHash(stark_felt!("0x2")),
+            block_number: BlockNumber(3),
+            l1_gas_price: GasPricePerToken { price_in_wei: GasPrice(4), price_in_fri: GasPrice(0) },
+            state_root: GlobalRoot(stark_felt!("0x5")),
+            sequencer: ContractAddress(patricia_key!("0x6")),
+            timestamp: BlockTimestamp(7),
+            ..BlockHeader::default()
+        }
+    );
+
+    let serialized = serde_json::to_value(&header).unwrap();
+    assert_eq!(serde_json::from_value::<BlockHeader>(serialized).unwrap(), header);
+}
@@ -0,0 +1,336 @@
+#[cfg(test)]
+#[path = "compiled_class_test.rs"]
+mod compiled_class_test;
+
+use std::collections::HashMap;
+
+use cairo_lang_sierra_to_casm::compiler::CompilationError;
+use cairo_lang_starknet_classes::allowed_libfuncs::ListSelector;
+/// The CASM types of the Cairo compiler that convert to [`CompiledClass`] and
+/// [`CompiledClassEntryPoint`], so that callers use the same compiler version as this crate.
+pub use cairo_lang_starknet_classes::casm_contract_class::{
+    CasmContractClass, CasmContractEntryPoint,
+};
+use cairo_lang_starknet_classes::casm_contract_class::{
+    CasmContractEntryPoints, StarknetSierraCompilationError,
+};
+use cairo_lang_starknet_classes::contract_class::ContractClass as CairoLangContractClass;
+use once_cell::sync::Lazy;
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+
+use crate::StarknetApiError;
+use crate::core::{CompiledClassHash, EntryPointSelector};
+use crate::deprecated_contract_class::EntryPoint as DeprecatedEntryPoint;
+use crate::hash::{
+    HashChain, Poseidon, StarkFelt, StarkHash, StarkHasher, ascii_as_felt, lazy_ascii_as_felt,
+};
+use crate::state::{ContractClass, EntryPoint, EntryPointType};
+
+static COMPILED_CLASS_V1: Lazy<StarkFelt> = lazy_ascii_as_felt!("COMPILED_CLASS_V1");
+
+/// A compiled (CASM) contract class, in the JSON form of the Cairo compiler.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct CompiledClass {
+    pub prime: U256,
+    pub compiler_version: String,
+    pub bytecode: Vec<StarkFelt>,
+    /// The lengths of the segments of the bytecode, or None if it is a single segment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytecode_segment_lengths: Option<NestedIntList>,
+    /// The hints of the program, as a list of (offset, hints) pairs. The hints are kept in their
+    /// JSON form, so that classes of later compilers, which add hints, can be read.
+    pub hints: Vec<(usize, Vec<serde_json::Value>)>,
+    pub entry_points_by_type: HashMap<EntryPointType, Vec<CompiledClassEntryPoint>>,
+}
+
+impl CompiledClass {
+    /// Returns the hash of the class, which is the Poseidon hash of:
+    /// ["COMPILED_CLASS_V1",
+    ///  Poseidon(selector, offset, Poseidon(builtins as ASCII strings), ...) of the external, L1
+    ///  handler and constructor entry points, in this order,
+    ///  the hash of the bytecode].
+    ///
+    /// The hash of the bytecode is the hash of its segment tree, see [`NestedIntList`]. Classes
+    /// without segment lengths, compiled before Starknet 0.13.2, are a single segment whose hash is
+    /// Poseidon(bytecode).
+    pub fn compiled_class_hash(&self) -> Result<CompiledClassHash, StarknetApiError> {
+        self.compiled_class_hash_with_hasher::<Poseidon>()
+    }
+
+    /// Returns the hash of the class as in [`CompiledClass::compiled_class_hash`], using `H`
+    /// instead of the Poseidon hash.
+    pub fn compiled_class_hash_with_hasher<H: StarkHasher>(
+        &self,
+    ) -> Result<CompiledClassHash, StarknetApiError> {
+        let mut hash_chain = HashChain::new().chain(&COMPILED_CLASS_V1);
+        for entry_point_type in
+            [EntryPointType::External, EntryPointType::L1Handler, EntryPointType::Constructor]
+        {
+            let mut entry_points_hash = HashChain::new();
+            for entry_point in
+                self.entry_points_by_type.get(&entry_point_type).into_iter().flatten()
+            {
+                let builtins = entry_point
+                    .builtins
+                    .iter()
+                    .map(|builtin| ascii_as_felt(builtin))
+                    .collect::<Result<Vec<_>, _>>()?;
+                entry_points_hash = entry_points_hash
+                    .chain(&entry_point.selector.0)
+                    .chain(&entry_point.offset.into())
+                    .chain(&H::hash_array(&builtins));
+            }
+            hash_chain = hash_chain.chain(&entry_points_hash.get_hash::<H>());
+        }
+        let compiled_class_hash = hash_chain.chain(&self.bytecode_hash::<H>()?).get_hash::<H>();
+        Ok(CompiledClassHash(compiled_class_hash))
+    }
+
+    fn bytecode_hash<H: StarkHasher>(&self) -> Result<StarkHash, StarknetApiError> {
+        let single_segment = NestedIntList::Leaf(self.bytecode.len());
+        let segment_lengths = self.bytecode_segment_lengths.as_ref().unwrap_or(&single_segment);
+        let mut bytecode = self.bytecode.as_slice();
+        let (_, hash) = bytecode_segment_hash::<H>(&mut bytecode, segment_lengths)
+            .ok_or(StarknetApiError::BytecodeSegmentLengthsMismatch(self.bytecode.len()))?;
+        if !bytecode.is_empty() {
+            return Err(StarknetApiError::BytecodeSegmentLengthsMismatch(self.bytecode.len()));
+        }
+        Ok(hash)
+    }
+}
+
+impl TryFrom<CasmContractClass> for CompiledClass {
+    type Error = StarknetApiError;
+
+    fn try_from(casm_contract_class: CasmContractClass) -> Result<Self, Self::Error> {
+        let prime = U256::from_str_radix(&casm_contract_class.prime.to_str_radix(16), 16)
+            .map_err(|error| StarknetApiError::ClassConversion(error.to_string()))?;
+        let bytecode = casm_contract_class
+            .bytecode
+            .iter()
+            .map(|felt| StarkFelt::try_from(felt.value.to_str_radix(16).as_str()))
+            .collect::<Result<_, _>>()?;
+        // The compiler does not export the type of its segment tree, which has the same JSON form.
+        let bytecode_segment_lengths = casm_contract_class
+            .bytecode_segment_lengths
+            .map(|segment_lengths| {
+                serde_json::to_value(segment_lengths).and_then(serde_json::from_value)
+            })
+            .transpose()
+            .map_err(|error| StarknetApiError::ClassConversion(error.to_string()))?;
+        let hints = casm_contract_class
+            .hints
+            .into_iter()
+            .map(|(offset, hints)| {
+                let hints = hints.iter().map(serde_json::to_value).collect::<Result<_, _>>()?;
+                Ok((offset, hints))
+            })
+            .collect::<Result<_, serde_json::Error>>()
+            .map_err(|error| StarknetApiError::ClassConversion(error.to_string()))?;
+
+        let CasmContractEntryPoints { external, l1_handler, constructor } =
+            casm_contract_class.entry_points_by_type;
+        let mut entry_points_by_type = HashMap::new();
+        for (entry_point_type, entry_points) in [
+            (EntryPointType::External, external),
+            (EntryPointType::L1Handler, l1_handler),
+            (EntryPointType::Constructor, constructor),
+        ] {
+            let entry_points = entry_points
+                .into_iter()
+                .map(CompiledClassEntryPoint::try_from)
+                .collect::<Result<_, _>>()?;
+            entry_points_by_type.insert(entry_point_type, entry_points);
+        }
+
+        Ok(Self {
+            prime,
+            compiler_version: casm_contract_class.compiler_version,
+            bytecode,
+            bytecode_segment_lengths,
+            hints,
+            entry_points_by_type,
+        })
+    }
+}
+
+/// The libfuncs that a class may use, by a list of the Cairo compiler.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub enum AllowedLibfuncs {
+    /// The default list of the compiler.
+    #[default]
+    Default,
+    /// A list of the compiler by its name, e.g., "audited".
+    ListName(String),
+    /// A list in a JSON file, by its path.
+    ListFile(String),
+}
+
+/// The version of the Sierra program of a class, which its first three felts encode.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct SierraVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl std::fmt::Display for SierraVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// The latest Sierra version that [`ContractClass::compile`] compiles, that of Cairo 2.6.4.
+pub const MAX_SIERRA_VERSION: SierraVersion = SierraVersion { major: 1, minor: 5, patch: 0 };
+
+/// Options for compiling a class by [`ContractClass::compile`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CompileOptions {
+    /// The maximal number of felts in the bytecode of the compiled class.
+    pub max_bytecode_size: usize,
+    pub allowed_libfuncs: AllowedLibfuncs,
+}
+
+impl ContractClass {
+    /// Returns the version of the Sierra program of the class.
+    pub fn sierra_version(&self) -> Result<SierraVersion, StarknetApiError> {
+        let Some([major, minor, patch]) = self.sierra_program.get(..3) else {
+            return Err(StarknetApiError::InvalidSierraProgram(
+                "the program does not start with its version".to_owned(),
+            ));
+        };
+        Ok(SierraVersion {
+            major: u64::try_from(*major)?,
+            minor: u64::try_from(*minor)?,
+            patch: u64::try_from(*patch)?,
+        })
+    }
+
+    /// Compiles the class to CASM with the Cairo compiler, after checking that it uses only allowed
+    /// libfuncs.
+    ///
+    /// The compiler is that of Cairo 2.6.4, which splits the bytecode into segments. Classes of
+    /// Sierra versions later than [`MAX_SIERRA_VERSION`] are rejected with
+    /// [`StarknetApiError::UnsupportedSierraVersion`]. The compiled class hash of the result is the
+    /// one that Starknet expects only if Starknet compiles the class with a compiler that emits the
+    /// same CASM.
+    pub fn compile(&self, options: CompileOptions) -> Result<CompiledClass, StarknetApiError> {
+        let sierra_version = self.sierra_version()?;
+        if sierra_version > MAX_SIERRA_VERSION {
+            return Err(StarknetApiError::UnsupportedSierraVersion {
+                version: sierra_version,
+                max_version: MAX_SIERRA_VERSION,
+            });
+        }
+        let contract_class = self.to_cairo_lang_contract_class()?;
+        contract_class
+            .extract_sierra_program()
+            .map_err(|error| StarknetApiError::InvalidSierraProgram(error.to_string()))?;
+        let list_selector = match options.allowed_libfuncs {
+            AllowedLibfuncs::Default => ListSelector::DefaultList,
+            AllowedLibfuncs::ListName(name) => ListSelector::ListName(name),
+            AllowedLibfuncs::ListFile(path) => ListSelector::ListFile(path),
+        };
+        contract_class
+            .validate_version_compatible(list_selector)
+            .map_err(|error| StarknetApiError::AllowedLibfuncs(error.to_string()))?;
+        let casm_contract_class = CasmContractClass::from_contract_class(
+            contract_class,
+            false,
+            options.max_bytecode_size,
+        )
+        .map_err(|error| match error {
+            StarknetSierraCompilationError::CompilationError(error)
+                if matches!(*error, CompilationError::CodeSizeLimitExceeded) =>
+            {
+                StarknetApiError::BytecodeSizeTooLarge {
+                    max_bytecode_size: options.max_bytecode_size,
+                }
+            }
+            error => StarknetApiError::SierraCompilation(error.to_string()),
+        })?;
+        CompiledClass::try_from(casm_contract_class)
+    }
+
+    // The class in the form of the Cairo compiler, without its ABI, which compilation does not
+    // need.
+    fn to_cairo_lang_contract_class(&self) -> Result<CairoLangContractClass, StarknetApiError> {
+        let entry_points_by_type: HashMap<EntryPointType, Vec<EntryPoint>> =
+            [EntryPointType::External, EntryPointType::L1Handler, EntryPointType::Constructor]
+                .into_iter()
+                .map(|entry_point_type| {
+                    let entry_points = self
+                        .entry_point_by_type
+                        .get(&entry_point_type)
+                        .cloned()
+                        .unwrap_or_default();
+                    (entry_point_type, entry_points)
+                })
+                .collect();
+        let contract_class = serde_json::json!({
+            "sierra_program": self.sierra_program,
+            "contract_class_version": self.contract_class_version,
+            "entry_points_by_type": entry_points_by_type,
+        });
+        serde_json::from_value(contract_class)
+            .map_err(|error| StarknetApiError::ClassConversion(error.to_string()))
+    }
+}
+
+/// An entry point of a [CompiledClass](`crate::compiled_class::CompiledClass`).
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct CompiledClassEntryPoint {
+    pub selector: EntryPointSelector,
+    /// The offset of the entry point in the bytecode.
+    pub offset: usize,
+    pub builtins: Vec<String>,
+}
+
+impl TryFrom<CasmContractEntryPoint> for CompiledClassEntryPoint {
+    type Error = StarknetApiError;
+
+    fn try_from(entry_point: CasmContractEntryPoint) -> Result<Self, Self::Error> {
+        let builtins = entry_point.builtins.clone();
+        let DeprecatedEntryPoint { selector, offset } = entry_point.try_into()?;
+        Ok(Self { selector, offset: offset.0, builtins })
+    }
+}
+
+/// The segment tree of a bytecode: a leaf is the length of a segment, and a node is a segment
+/// made of the segments of its children.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum NestedIntList {
+    Leaf(usize),
+    Node(Vec<NestedIntList>),
+}
+
+// Consumes a segment from the start of the bytecode and returns its length and hash: H(data) for a
+// leaf and 1 + H(length0, hash0, length1, hash1, ...) of the children for a node. Returns None if
+// the bytecode is too short.
+fn bytecode_segment_hash<H: StarkHasher>(
+    bytecode: &mut &[StarkFelt],
+    segment_lengths: &NestedIntList,
+) -> Option<(usize, StarkHash)> {
+    match segment_lengths {
+        NestedIntList::Leaf(length) => {
+            if *length > bytecode.len() {
+                return None;
+            }
+            let (segment, rest) = bytecode.split_at(*length);
+            *bytecode = rest;
+            Some((*length, H::hash_array(segment)))
+        }
+        NestedIntList::Node(children) => {
+            let mut length = 0;
+            let mut hash_chain = HashChain::new();
+            for child in children {
+                let (child_length, child_hash) = bytecode_segment_hash::<H>(bytecode, child)?;
+                length += child_length;
+                hash_chain = hash_chain.chain(&child_length.into()).chain(&child_hash);
+            }
+            Some((length, hash_chain.get_hash::<H>() + StarkFelt::ONE))
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use assert_matches::assert_matches;
+use serde_json::json;
+
+use crate::compiled_class::{
+    AllowedLibfuncs, CasmContractClass, CompileOptions, CompiledClass, MAX_SIERRA_VERSION,
+    NestedIntList, SierraVersion,
+};
+use crate::core::CompiledClassHash;
+use crate::hash::{
+    Pedersen, Poseidon, StarkFelt, StarkHash, StarkHasher, ascii_as_felt, poseidon_hash_many,
+};
+use crate::state::ContractClass;
+use crate::test_utils::{get_compile_options, get_minimal_contract_class};
+use crate::{StarknetApiError, stark_felt};
+
+fn get_compiled_class_json() -> serde_json::Value {
+    json!({
+        "prime": "0x800000000000011000000000000000000000000000000000000000000000001",
+        "compiler_version": "2.6.0",
+        "bytecode": ["0xa0680017fff8000", "0x7", "0x482680017ffa8000", "0x1", "0x208b7fff7fff7ffe"],
+        "bytecode_segment_lengths": [2, [1, 2]],
+        "hints": [[0, [{"TestLessThanOrEqual": {
+            "lhs": {"Immediate": "0x0"},
+            "rhs": {"Deref": {"register": "FP", "offset": -6}},
+            "dst": {"register": "AP", "offset": 0},
+        }}]]],
+        "entry_points_by_type": {
+            "EXTERNAL": [{"selector": "0x1", "offset": 0, "builtins": ["pedersen", "range_check"]}],
+            "L1_HANDLER": [],
+            "CONSTRUCTOR": [{"selector": "0x2", "offset": 2, "builtins": []}],
+        },
+    })
+}
+
+fn get_bytecode() -> Vec<StarkFelt> {
+    vec![
+        stark_felt!("0xa0680017fff8000"),
+        stark_felt!("0x7"),
+        stark_felt!("0x482680017ffa8000"),
+        stark_felt!("0x1"),
+        stark_felt!("0x208b7fff7fff7ffe"),
+    ]
+}
+
+fn get_entry_points_hashes<H: StarkHasher>() -> [StarkFelt; 3] {
+    let builtins_hash =
+        H::hash_array(&[ascii_as_felt("pedersen").unwrap(), ascii_as_felt("range_check").unwrap()]);
+    [
+        H::hash_array(&[stark_felt!(1_u8), StarkFelt::ZERO, builtins_hash]),
+        H::hash_array(&[]),
+        H::hash_array(&[stark_felt!(2_u8), stark_felt!(2_u8), H::hash_array(&[])]),
+    ]
+}
+
+// The hash of the class with its segments, with H instead of the Poseidon hash.
+fn get_compiled_class_hash_with_segments<H: StarkHasher>() -> StarkHash {
+    let bytecode = get_bytecode();
+    // The segments are [2, [1, 2]].
+    let inner_node_hash = H::hash_array(&[
+        stark_felt!(1_u8),
+        H::hash_array(&bytecode[2..3]),
+        stark_felt!(2_u8),
+        H::hash_array(&bytecode[3..]),
+    ]) + StarkFelt::ONE;
+    let bytecode_hash = H::hash_array(&[
+        stark_felt!(2_u8),
+        H::hash_array(&bytecode[..2]),
+        stark_felt!(3_u8),
+        inner_node_hash,
+    ]) + StarkFelt::ONE;
+    let [external, l1_handler, constructor] = get_entry_points_hashes::<H>();
+    H::hash_array(&[
+        ascii_as_felt("COMPILED_CLASS_V1").unwrap(),
+        external,
+        l1_handler,
+        constructor,
+        bytecode_hash,
+    ])
+}
+
+#[test]
+fn compiled_class_hash_with_segments() {
+    let class: CompiledClass = serde_json::from_value(get_compiled_class_json()).unwrap();
+    let expected = get_compiled_class_hash_with_segments::<Poseidon>();
+    assert_eq!(class.compiled_class_hash().unwrap(), CompiledClassHash(expected));
+    // As computed by the Cairo compiler.
+    assert_eq!(
+        expected,
+        stark_felt!("0x63055f6e28e63cbfc3295f567bea7cad420829feb7a30d3558ee5ad3b77bd34")
+    );
+
+    assert_eq!(
+        class.compiled_class_hash_with_hasher::<Pedersen>().unwrap(),
+        CompiledClassHash(get_compiled_class_hash_with_segments::<Pedersen>())
+    );
+}
+
+#[test]
+fn compiled_class_hash_without_segments() {
+    let mut class: CompiledClass = serde_json::from_value(get_compiled_class_json()).unwrap();
+    class.bytecode_segment_lengths = None;
+
+    let [external, l1_handler, constructor] = get_entry_points_hashes::<Poseidon>();
+    let expected = poseidon_hash_many(&[
+        ascii_as_felt("COMPILED_CLASS_V1").unwrap(),
+        external,
+        l1_handler,
+        constructor,
+        poseidon_hash_many(&get_bytecode()),
+    ]);
+    assert_eq!(class.compiled_class_hash().unwrap(), CompiledClassHash(expected));
+    // As computed by the Cairo compiler.
+    assert_eq!(
+        expected,
+        stark_felt!("0xcceffb671557f22f07607c61f69181c177154fdaadeb210d47361d3929e365")
+    );
+}
+
+#[test]
+fn compiled_class_hash_segment_lengths_mismatch() {
+    let mut class: CompiledClass = serde_json::from_value(get_compiled_class_json()).unwrap();
+    for segment_lengths in [NestedIntList::Leaf(4), NestedIntList::Leaf(6)] {
+        class.bytecode_segment_lengths = Some(segment_lengths);
+        assert_matches!(
+            class.compiled_class_hash(),
+            Err(StarknetApiError::BytecodeSegmentLengthsMismatch(5))
+        );
+    }
+}
+
+#[test]
+fn compiled_class_invalid_hints() {
+    for hints in [json!({}), json!([0]), json!([[0, {}]]), json!([["0x0", []]])] {
+        let mut class_json = get_compiled_class_json();
+        class_json["hints"] = hints;
+        assert!(serde_json::from_value::<CompiledClass>(class_json).is_err());
+    }
+}
+
+#[test]
+fn compiled_class_from_casm_contract_class() {
+    let mut without_segments = get_compiled_class_json();
+    without_segments.as_object_mut().unwrap().remove("bytecode_segment_lengths");
+    for class_json in [get_compiled_class_json(), without_segments] {
+        let casm_contract_class: CasmContractClass =
+            serde_json::from_value(class_json.clone()).unwrap();
+        let class = CompiledClass::try_from(casm_contract_class).unwrap();
+        assert_eq!(class, serde_json::from_value(class_json).unwrap());
+    }
+}
+
+#[test]
+fn compile_contract_class() {
+    let class = get_minimal_contract_class().compile(get_compile_options()).unwrap();
+    assert_eq!(class.bytecode.len(), 89);
+    assert_eq!(
+        class.bytecode_segment_lengths,
+        Some(NestedIntList::Node(vec![NestedIntList::Leaf(89)]))
+    );
+    // The hash that the Cairo compiler gives the compiled class in its test data.
+    assert_eq!(
+        class.compiled_class_hash().unwrap(),
+        CompiledClassHash(stark_felt!(
+            "0x5fd87fec0614148ce800defb1ad3dbb9212451672e244d24fb1aa359e342055"
+        ))
+    );
+}
+
+#[test]
+fn compile_bytecode_size_too_large() {
+    let options = CompileOptions { max_bytecode_size: 88, ..get_compile_options() };
+    assert_matches!(
+        get_minimal_contract_class().compile(options),
+        Err(StarknetApiError::BytecodeSizeTooLarge { max_bytecode_size: 88 })
+    );
+}
+
+#[test]
+fn compile_unknown_allowed_libfuncs_list() {
+    let options = CompileOptions {
+        allowed_libfuncs: AllowedLibfuncs::ListName("no_such_list".to_owned()),
+        ..get_compile_options()
+    };
+    assert_matches!(
+        get_minimal_contract_class().compile(options),
+        Err(StarknetApiError::AllowedLibfuncs(message)) if message.contains("no_such_list")
+    );
+}
+
+#[test]
+fn compile_invalid_sierra_program() {
+    let class =
+        ContractClass { sierra_program: vec![stark_felt!(1_u64)], ..ContractClass::default() };
+    assert_matches!(
+        class.compile(get_compile_options()),
+        Err(StarknetApiError::InvalidSierraProgram(_))
+    );
+}
+
+#[test]
+fn compile_unsupported_sierra_version() {
+    let mut class = get_minimal_contract_class();
+    assert_eq!(class.sierra_version().unwrap(), MAX_SIERRA_VERSION);
+    class.sierra_program[1] = stark_felt!(6_u64);
+    assert_matches!(
+        class.compile(get_compile_options()),
+        Err(StarknetApiError::UnsupportedSierraVersion { version, max_version })
+        if version == SierraVersion { major: 1, minor: 6, patch: 0 }
+            && max_version == MAX_SIERRA_VERSION
+    );
+}
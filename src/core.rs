@@ -10,10 +10,10 @@ use primitive_types::H160;
 use serde::{Deserialize, Serialize};
 use starknet_crypto::FieldElement;
 
-use crate::hash::{pedersen_hash_array, StarkFelt, StarkHash};
+use crate::hash::{Pedersen, StarkFelt, StarkHash, StarkHasher, ascii_as_felt, starknet_keccak};
 use crate::serde_utils::{BytesAsHex, PrefixedBytesAsHex};
 use crate::transaction::{Calldata, ContractAddressSalt};
-use crate::{impl_from_through_intermediate, StarknetApiError};
+use crate::{StarknetApiError, impl_from_through_intermediate};
 
 /// A chain id.
 #[derive(Clone, Debug, Display, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
@@ -62,17 +62,33 @@ impl TryFrom<StarkHash> for ContractAddress {
     }
 }
 
-// TODO: Add a hash_function as a parameter
+/// Calculates the address of a contract deployed by `deployer_address`, as defined
+/// in <https://docs.starknet.io/documentation/architecture_and_concepts/Smart_Contracts/contract-address/.>
 pub fn calculate_contract_address(
     salt: ContractAddressSalt,
     class_hash: ClassHash,
     constructor_calldata: &Calldata,
     deployer_address: ContractAddress,
 ) -> Result<ContractAddress, StarknetApiError> {
-    let constructor_calldata_hash = pedersen_hash_array(&constructor_calldata.0);
-    let contract_address_prefix = format!("0x{}", hex::encode(CONTRACT_ADDRESS_PREFIX));
-    let mut address = FieldElement::from(pedersen_hash_array(&[
-        StarkFelt::try_from(contract_address_prefix.as_str())?,
+    calculate_contract_address_with_hasher::<Pedersen>(
+        salt,
+        class_hash,
+        constructor_calldata,
+        deployer_address,
+    )
+}
+
+/// Calculates the address of a contract as in [`calculate_contract_address`], using `H` instead of
+/// the Pedersen hash.
+pub fn calculate_contract_address_with_hasher<H: StarkHasher>(
+    salt: ContractAddressSalt,
+    class_hash: ClassHash,
+    constructor_calldata: &Calldata,
+    deployer_address: ContractAddress,
+) -> Result<ContractAddress, StarknetApiError> {
+    let constructor_calldata_hash = H::hash_array(&constructor_calldata.0);
+    let mut address = FieldElement::from(H::hash_array(&[
+        ascii_as_felt(CONTRACT_ADDRESS_PREFIX)?,
         *deployer_address.0.key(),
         salt.0,
         class_hash.0,
@@ -129,6 +145,36 @@ pub struct Nonce(pub StarkFelt);
 )]
 pub struct EntryPointSelector(pub StarkHash);
 
+impl EntryPointSelector {
+    /// Returns the selector of the entry point with the given name.
+    pub fn from_name(name: &str) -> Self {
+        if name == DEFAULT_ENTRY_POINT_NAME || name == DEFAULT_L1_ENTRY_POINT_NAME {
+            return Self(StarkFelt::ZERO);
+        }
+        Self(starknet_keccak(name.as_bytes()))
+    }
+}
+
+/// The name of the fallback entry point, whose selector is zero.
+pub const DEFAULT_ENTRY_POINT_NAME: &str = "__default__";
+/// The name of the fallback L1 handler entry point, whose selector is zero.
+pub const DEFAULT_L1_ENTRY_POINT_NAME: &str = "__l1_default__";
+/// The name of the entry point that executes the calls of an account transaction.
+pub const EXECUTE_ENTRY_POINT_NAME: &str = "__execute__";
+/// The name of the entry point that validates an account transaction.
+pub const VALIDATE_ENTRY_POINT_NAME: &str = "__validate__";
+/// The name of the constructor entry point.
+pub const CONSTRUCTOR_ENTRY_POINT_NAME: &str = "constructor";
+/// The selector of the [`EXECUTE_ENTRY_POINT_NAME`] entry point.
+pub static EXECUTE_ENTRY_POINT_SELECTOR: Lazy<EntryPointSelector> =
+    Lazy::new(|| EntryPointSelector::from_name(EXECUTE_ENTRY_POINT_NAME));
+/// The selector of the [`VALIDATE_ENTRY_POINT_NAME`] entry point.
+pub static VALIDATE_ENTRY_POINT_SELECTOR: Lazy<EntryPointSelector> =
+    Lazy::new(|| EntryPointSelector::from_name(VALIDATE_ENTRY_POINT_NAME));
+/// The selector of the [`CONSTRUCTOR_ENTRY_POINT_NAME`] entry point.
+pub static CONSTRUCTOR_ENTRY_POINT_SELECTOR: Lazy<EntryPointSelector> =
+    Lazy::new(|| EntryPointSelector::from_name(CONSTRUCTOR_ENTRY_POINT_NAME));
+
 /// The root of the global state at a [Block](`crate::block::Block`)
 /// and [StateUpdate](`crate::state::StateUpdate`).
 #[derive(
@@ -236,6 +282,15 @@ impl TryFrom<StarkFelt> for EthAddress {
     }
 }
 
+impl From<EthAddress> for StarkFelt {
+    fn from(address: EthAddress) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes[32 - H160::len_bytes()..].copy_from_slice(address.0.as_bytes());
+        // A 160-bit value is always in the field.
+        StarkFelt::new_unchecked(bytes)
+    }
+}
+
 impl TryFrom<PrefixedBytesAsHex<20_usize>> for EthAddress {
     type Error = StarknetApiError;
     fn try_from(val: PrefixedBytesAsHex<20_usize>) -> Result<Self, Self::Error> {
@@ -2,10 +2,12 @@ use assert_matches::assert_matches;
 use starknet_crypto::FieldElement;
 
 use crate::core::{
-    calculate_contract_address, ClassHash, ContractAddress, EthAddress, PatriciaKey,
-    StarknetApiError, CONTRACT_ADDRESS_PREFIX, L2_ADDRESS_UPPER_BOUND,
+    CONSTRUCTOR_ENTRY_POINT_SELECTOR, CONTRACT_ADDRESS_PREFIX, ClassHash, ContractAddress,
+    DEFAULT_ENTRY_POINT_NAME, EXECUTE_ENTRY_POINT_SELECTOR, EntryPointSelector, EthAddress,
+    L2_ADDRESS_UPPER_BOUND, PatriciaKey, StarknetApiError, VALIDATE_ENTRY_POINT_SELECTOR,
+    calculate_contract_address, calculate_contract_address_with_hasher,
 };
-use crate::hash::{pedersen_hash_array, StarkFelt, StarkHash};
+use crate::hash::{Poseidon, StarkFelt, StarkHash, pedersen_hash_array, poseidon_hash_many};
 use crate::transaction::{Calldata, ContractAddressSalt};
 use crate::{class_hash, patricia_key, stark_felt};
 
@@ -66,6 +68,35 @@ fn test_calculate_contract_address() {
     assert_eq!(actual_address, expected_address);
 }
 
+#[test]
+fn test_calculate_contract_address_with_poseidon() {
+    let salt = ContractAddressSalt(stark_felt!(1337_u16));
+    let class_hash = class_hash!("0x110");
+    let deployer_address = ContractAddress::from(2_u8);
+    let constructor_calldata = Calldata(vec![stark_felt!(60_u16), stark_felt!(70_u16)].into());
+
+    let actual_address = calculate_contract_address_with_hasher::<Poseidon>(
+        salt,
+        class_hash,
+        &constructor_calldata,
+        deployer_address,
+    )
+    .unwrap();
+
+    let address = poseidon_hash_many(&[
+        StarkFelt::try_from(format!("0x{}", hex::encode(CONTRACT_ADDRESS_PREFIX)).as_str())
+            .unwrap(),
+        *deployer_address.0.key(),
+        salt.0,
+        class_hash.0,
+        poseidon_hash_many(&constructor_calldata.0),
+    ]);
+    let mod_address = FieldElement::from(address) % *L2_ADDRESS_UPPER_BOUND;
+    let expected_address = ContractAddress::try_from(StarkFelt::from(mod_address)).unwrap();
+
+    assert_eq!(actual_address, expected_address);
+}
+
 #[test]
 fn eth_address_serde() {
     let eth_address = EthAddress::try_from(StarkFelt::try_from("0x001").unwrap()).unwrap();
@@ -75,3 +106,35 @@ fn eth_address_serde() {
     let restored = serde_json::from_str::<EthAddress>(&serialized).unwrap();
     assert_eq!(restored, eth_address);
 }
+
+#[test]
+fn entry_point_selector_from_name() {
+    assert_eq!(
+        EntryPointSelector::from_name("transfer"),
+        EntryPointSelector(stark_felt!(
+            "0x83afd3f4caedc6eebf44246fe54e38c95e3179a5ec9ea81740eca5b482d12e"
+        ))
+    );
+    assert_eq!(
+        *EXECUTE_ENTRY_POINT_SELECTOR,
+        EntryPointSelector(stark_felt!(
+            "0x15d40a3d6ca2ac30f4031e42be28da9b056fef9bb7357ac5e85627ee876e5ad"
+        ))
+    );
+    assert_eq!(
+        *VALIDATE_ENTRY_POINT_SELECTOR,
+        EntryPointSelector(stark_felt!(
+            "0x162da33a4585851fe8d3af3c2a9c60b557814e221e0d4f30ff0b2189d9c7775"
+        ))
+    );
+    assert_eq!(
+        *CONSTRUCTOR_ENTRY_POINT_SELECTOR,
+        EntryPointSelector(stark_felt!(
+            "0x28ffe4ff0f226a9107253e17a904099aa4f63a02a5621de0576e5aa71bc5194"
+        ))
+    );
+    assert_eq!(
+        EntryPointSelector::from_name(DEFAULT_ENTRY_POINT_NAME),
+        EntryPointSelector::default()
+    );
+}
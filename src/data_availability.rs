@@ -0,0 +1,201 @@
+#[cfg(test)]
+#[path = "data_availability_test.rs"]
+mod data_availability_test;
+
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+
+use crate::StarknetApiError;
+use crate::hash::{StarkFelt, mod_add, mod_mul, mod_pow, mod_sub};
+
+/// The data availability mode of the nonce or the fee of a transaction; i.e., whether it is
+/// published on L1 or kept on L2.
+#[derive(
+    Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord,
+)]
+pub enum DataAvailabilityMode {
+    #[serde(rename = "L1")]
+    #[default]
+    L1 = 0,
+    #[serde(rename = "L2")]
+    L2 = 1,
+}
+
+impl From<DataAvailabilityMode> for StarkFelt {
+    fn from(data_availability_mode: DataAvailabilityMode) -> Self {
+        Self::from(data_availability_mode as u8)
+    }
+}
+
+/// The mode by which a block publishes its state diff on L1.
+#[derive(
+    Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord,
+)]
+pub enum L1DataAvailabilityMode {
+    /// As calldata of the L1 transaction.
+    #[serde(rename = "CALLDATA")]
+    #[default]
+    Calldata,
+    /// As a blob, see <https://eips.ethereum.org/EIPS/eip-4844>.
+    #[serde(rename = "BLOB")]
+    Blob,
+}
+
+/// The number of field elements in a blob.
+pub const FIELD_ELEMENTS_PER_BLOB: usize = 4096;
+/// The number of bytes in a field element of a blob, and in a word of calldata.
+pub const BYTES_PER_FIELD_ELEMENT: usize = 32;
+/// The number of bytes in a blob.
+pub const BYTES_PER_BLOB: usize = FIELD_ELEMENTS_PER_BLOB * BYTES_PER_FIELD_ELEMENT;
+
+// The modulus of the scalar field of BLS12-381, in which the elements of a blob are.
+const BLS_MODULUS: U256 = U256([
+    0xffff_ffff_0000_0001,
+    0x53bd_a402_fffe_5bfe,
+    0x3339_d808_09a1_d805,
+    0x73ed_a753_299d_7d48,
+]);
+// A generator of the multiplicative group of the BLS12-381 scalar field.
+const BLS_MULTIPLICATIVE_GENERATOR: u8 = 7;
+
+/// Packs felts into blobs, 4096 felts per blob with the last one padded with zeros, and returns
+/// the concatenated blobs.
+///
+/// The felts of a blob are the coefficients of a polynomial, and the blob holds its evaluations on
+/// the 4096th roots of unity of the BLS12-381 scalar field in bit-reversed order, each as 32
+/// big-endian bytes.
+pub fn felts_to_blobs(felts: &[StarkFelt]) -> Vec<u8> {
+    let root_of_unity = blob_root_of_unity();
+    let mut blobs = Vec::new();
+    for chunk in felts.chunks(FIELD_ELEMENTS_PER_BLOB) {
+        let mut values: Vec<U256> =
+            chunk.iter().map(|felt| U256::from_big_endian(felt.bytes())).collect();
+        values.resize(FIELD_ELEMENTS_PER_BLOB, U256::zero());
+        fft(&mut values, root_of_unity);
+        bit_reverse_permutation(&mut values);
+        for value in values {
+            let mut bytes = [0u8; BYTES_PER_FIELD_ELEMENT];
+            value.to_big_endian(&mut bytes);
+            blobs.extend_from_slice(&bytes);
+        }
+    }
+    blobs
+}
+
+/// Unpacks the felts of blobs packed by [`felts_to_blobs`], including the padding.
+pub fn blobs_to_felts(blobs: &[u8]) -> Result<Vec<StarkFelt>, StarknetApiError> {
+    let blob_chunks = blobs.chunks_exact(BYTES_PER_BLOB);
+    if !blob_chunks.remainder().is_empty() {
+        return Err(invalid_encoding(format!(
+            "got {} bytes, which is not a whole number of blobs",
+            blobs.len()
+        )));
+    }
+    let inverse_root_of_unity =
+        mod_pow(blob_root_of_unity(), U256::from(FIELD_ELEMENTS_PER_BLOB - 1), BLS_MODULUS);
+    let inverse_size = mod_pow(U256::from(FIELD_ELEMENTS_PER_BLOB), BLS_MODULUS - 2, BLS_MODULUS);
+    let mut felts = Vec::with_capacity(blobs.len() / BYTES_PER_FIELD_ELEMENT);
+    for blob in blob_chunks {
+        let mut values = Vec::with_capacity(FIELD_ELEMENTS_PER_BLOB);
+        for bytes in blob.chunks(BYTES_PER_FIELD_ELEMENT) {
+            let value = U256::from_big_endian(bytes);
+            if value >= BLS_MODULUS {
+                return Err(invalid_encoding(format!(
+                    "blob element 0x{} is not in the BLS12-381 scalar field",
+                    hex::encode(bytes)
+                )));
+            }
+            values.push(value);
+        }
+        bit_reverse_permutation(&mut values);
+        fft(&mut values, inverse_root_of_unity);
+        for value in values {
+            let mut bytes = [0u8; BYTES_PER_FIELD_ELEMENT];
+            mod_mul(value, inverse_size, BLS_MODULUS).to_big_endian(&mut bytes);
+            felts.push(StarkFelt::new(bytes).map_err(|_| {
+                invalid_encoding(format!(
+                    "blob coefficient 0x{} is not in the Starknet field",
+                    hex::encode(bytes)
+                ))
+            })?);
+        }
+    }
+    Ok(felts)
+}
+
+/// Returns the calldata words of felts, each as 32 big-endian bytes.
+pub fn felts_to_calldata(felts: &[StarkFelt]) -> Vec<u8> {
+    felts.iter().flat_map(|felt| felt.bytes().iter().copied()).collect()
+}
+
+/// Returns the felts of calldata words built by [`felts_to_calldata`].
+pub fn calldata_to_felts(calldata: &[u8]) -> Result<Vec<StarkFelt>, StarknetApiError> {
+    let words = calldata.chunks_exact(BYTES_PER_FIELD_ELEMENT);
+    if !words.remainder().is_empty() {
+        return Err(invalid_encoding(format!(
+            "got {} bytes, which is not a whole number of words",
+            calldata.len()
+        )));
+    }
+    words
+        .map(|word| {
+            let bytes: [u8; BYTES_PER_FIELD_ELEMENT] =
+                word.try_into().expect("A chunk should be of size 32.");
+            StarkFelt::new(bytes).map_err(|_| {
+                invalid_encoding(format!(
+                    "word 0x{} is not in the Starknet field",
+                    hex::encode(word)
+                ))
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn invalid_encoding(reason: String) -> StarknetApiError {
+    StarknetApiError::InvalidDataAvailabilityEncoding(reason)
+}
+
+// A primitive 4096th root of unity: g^((r - 1) / 4096), where g generates the multiplicative group.
+fn blob_root_of_unity() -> U256 {
+    mod_pow(
+        U256::from(BLS_MULTIPLICATIVE_GENERATOR),
+        (BLS_MODULUS - 1) / U256::from(FIELD_ELEMENTS_PER_BLOB),
+        BLS_MODULUS,
+    )
+}
+
+// Replaces the coefficients of a polynomial, in place, by its evaluations on the powers of the
+// given root of unity, whose order is the number of coefficients.
+fn fft(values: &mut [U256], root_of_unity: U256) {
+    let size = values.len();
+    bit_reverse_permutation(values);
+    let mut half_size = 1;
+    while half_size < size {
+        let step = mod_pow(root_of_unity, U256::from(size / (2 * half_size)), BLS_MODULUS);
+        for start in (0..size).step_by(2 * half_size) {
+            let mut twiddle = U256::one();
+            for i in start..start + half_size {
+                let even = values[i];
+                let odd = mod_mul(values[i + half_size], twiddle, BLS_MODULUS);
+                values[i] = mod_add(even, odd, BLS_MODULUS);
+                values[i + half_size] = mod_sub(even, odd, BLS_MODULUS);
+                twiddle = mod_mul(twiddle, step, BLS_MODULUS);
+            }
+        }
+        half_size *= 2;
+    }
+}
+
+// Swaps every element with the one at the bit-reversal of its index. The length is a power of 2.
+fn bit_reverse_permutation<T>(values: &mut [T]) {
+    let index_bits = values.len().trailing_zeros();
+    if index_bits == 0 {
+        return;
+    }
+    for i in 0..values.len() {
+        let reversed = i.reverse_bits() >> (usize::BITS - index_bits);
+        if i < reversed {
+            values.swap(i, reversed);
+        }
+    }
+}
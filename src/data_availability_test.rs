@@ -0,0 +1,93 @@
+use assert_matches::assert_matches;
+use primitive_types::U256;
+
+use crate::data_availability::{
+    BLS_MODULUS, BYTES_PER_BLOB, BYTES_PER_FIELD_ELEMENT, FIELD_ELEMENTS_PER_BLOB,
+    blob_root_of_unity, blobs_to_felts, calldata_to_felts, felts_to_blobs, felts_to_calldata,
+};
+use crate::hash::{StarkFelt, mod_add, mod_mul, mod_pow};
+use crate::{StarknetApiError, stark_felt};
+
+fn blob_element(blobs: &[u8], index: usize) -> U256 {
+    U256::from_big_endian(&blobs[index * BYTES_PER_FIELD_ELEMENT..][..BYTES_PER_FIELD_ELEMENT])
+}
+
+#[test]
+fn blob_root_of_unity_order() {
+    let root_of_unity = blob_root_of_unity();
+    assert_eq!(
+        mod_pow(root_of_unity, U256::from(FIELD_ELEMENTS_PER_BLOB / 2), BLS_MODULUS),
+        BLS_MODULUS - 1
+    );
+    assert_eq!(
+        mod_pow(root_of_unity, U256::from(FIELD_ELEMENTS_PER_BLOB), BLS_MODULUS),
+        U256::one()
+    );
+}
+
+#[test]
+fn felts_to_blobs_evaluates_in_bit_reversed_order() {
+    // The polynomial 1 + 2x + 3x^2.
+    let felts = [stark_felt!(1_u8), stark_felt!(2_u8), stark_felt!(3_u8)];
+    let blobs = felts_to_blobs(&felts);
+    assert_eq!(blobs.len(), BYTES_PER_BLOB);
+
+    // Index 0 holds the evaluation at 1, and index 1 at the root of unity to the power of
+    // bitreverse(1) = 2048, which is -1.
+    assert_eq!(blob_element(&blobs, 0), U256::from(6));
+    assert_eq!(blob_element(&blobs, 1), U256::from(2));
+    // Index 2 holds the evaluation at the root of unity to the power of bitreverse(2) = 1024.
+    let x = mod_pow(blob_root_of_unity(), U256::from(1024), BLS_MODULUS);
+    let expected = mod_add(
+        U256::one(),
+        mod_add(
+            mod_mul(U256::from(2), x, BLS_MODULUS),
+            mod_mul(U256::from(3), mod_mul(x, x, BLS_MODULUS), BLS_MODULUS),
+            BLS_MODULUS,
+        ),
+        BLS_MODULUS,
+    );
+    assert_eq!(blob_element(&blobs, 2), expected);
+
+    let mut expected_felts = felts.to_vec();
+    expected_felts.resize(FIELD_ELEMENTS_PER_BLOB, StarkFelt::ZERO);
+    assert_eq!(blobs_to_felts(&blobs).unwrap(), expected_felts);
+}
+
+#[test]
+fn felts_to_blobs_multiple_blobs() {
+    let felts: Vec<StarkFelt> =
+        (0..FIELD_ELEMENTS_PER_BLOB + 1).map(|i| stark_felt!(i as u64 + 7)).collect();
+    let blobs = felts_to_blobs(&felts);
+    assert_eq!(blobs.len(), 2 * BYTES_PER_BLOB);
+
+    let decoded = blobs_to_felts(&blobs).unwrap();
+    assert_eq!(decoded[..felts.len()], felts[..]);
+    assert!(decoded[felts.len()..].iter().all(|felt| *felt == StarkFelt::ZERO));
+}
+
+#[test]
+fn blobs_to_felts_invalid_blobs() {
+    assert_matches!(
+        blobs_to_felts(&[0u8; BYTES_PER_BLOB - 1]),
+        Err(StarknetApiError::InvalidDataAvailabilityEncoding(_))
+    );
+    let mut blobs = vec![0u8; BYTES_PER_BLOB];
+    blobs[..BYTES_PER_FIELD_ELEMENT].fill(u8::MAX);
+    assert_matches!(
+        blobs_to_felts(&blobs),
+        Err(StarknetApiError::InvalidDataAvailabilityEncoding(_))
+    );
+}
+
+#[test]
+fn calldata_round_trip() {
+    let felts = [stark_felt!("0x1234"), StarkFelt::ZERO, stark_felt!(u64::MAX)];
+    let calldata = felts_to_calldata(&felts);
+    assert_eq!(calldata.len(), felts.len() * BYTES_PER_FIELD_ELEMENT);
+    assert_eq!(calldata_to_felts(&calldata).unwrap(), felts);
+    assert_matches!(
+        calldata_to_felts(&calldata[1..]),
+        Err(StarknetApiError::InvalidDataAvailabilityEncoding(_))
+    );
+}
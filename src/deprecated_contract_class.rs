@@ -1,13 +1,21 @@
+#[cfg(test)]
+#[path = "deprecated_contract_class_test.rs"]
+mod deprecated_contract_class_test;
+
 use std::collections::HashMap;
 
-use cairo_lang_starknet::casm_contract_class::CasmContractEntryPoint;
 use serde::de::Error as DeserializationError;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
 
-use crate::core::EntryPointSelector;
-use crate::serde_utils::deserialize_optional_contract_class_abi_entry_vector;
 use crate::StarknetApiError;
+use crate::compiled_class::CasmContractEntryPoint;
+use crate::core::{ClassHash, EntryPointSelector};
+use crate::hash::{Pedersen, StarkFelt, StarkHash, StarkHasher, ascii_as_felt, starknet_keccak};
+use crate::serde_utils::{deserialize_optional_contract_class_abi_entry_vector, python_json_dumps};
+
+// The version of the hash scheme of deprecated classes.
+const API_VERSION: StarkFelt = StarkFelt::ZERO;
 
 /// A deprecated contract class.
 #[derive(Debug, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
@@ -22,6 +30,158 @@ pub struct ContractClass {
     pub entry_points_by_type: HashMap<EntryPointType, Vec<EntryPoint>>,
 }
 
+impl ContractClass {
+    /// Returns the hash of the class, which is the Pedersen hash, as by
+    /// [`pedersen_hash_array`](crate::hash::pedersen_hash_array), of: [0,
+    ///  H(selector, offset, ...) of the external, L1 handler and constructor entry points, in this
+    ///  order,
+    ///  H(builtins as ASCII strings),
+    ///  the hinted class hash, see [`ContractClass::hinted_class_hash`],
+    ///  H(program data)],
+    /// where H is also the Pedersen hash of an array.
+    pub fn class_hash(&self) -> Result<ClassHash, StarknetApiError> {
+        self.class_hash_with_hasher::<Pedersen>()
+    }
+
+    /// Returns the hash of the class as in [`ContractClass::class_hash`], using `H` instead of the
+    /// Pedersen hash.
+    pub fn class_hash_with_hasher<H: StarkHasher>(&self) -> Result<ClassHash, StarknetApiError> {
+        let mut hash_elements = vec![API_VERSION];
+        for entry_point_type in
+            [EntryPointType::External, EntryPointType::L1Handler, EntryPointType::Constructor]
+        {
+            let entry_points: Vec<StarkFelt> = self
+                .entry_points_by_type
+                .get(&entry_point_type)
+                .into_iter()
+                .flatten()
+                .flat_map(|entry_point| [entry_point.selector.0, entry_point.offset.into()])
+                .collect();
+            hash_elements.push(H::hash_array(&entry_points));
+        }
+
+        let builtins = program_array(&self.program.builtins, "builtins")?
+            .iter()
+            .map(|builtin| match builtin {
+                Value::String(builtin) => ascii_as_felt(builtin),
+                _ => Err(invalid_program(format!("builtin {builtin} is not a string"))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        hash_elements.push(H::hash_array(&builtins));
+        hash_elements.push(self.hinted_class_hash());
+
+        let data = program_array(&self.program.data, "data")?
+            .iter()
+            .map(|felt| match felt {
+                Value::String(felt) => StarkFelt::try_from(felt.as_str()),
+                _ => Err(invalid_program(format!("data {felt} is not a hex string"))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        hash_elements.push(H::hash_array(&data));
+        Ok(ClassHash(H::hash_array(&hash_elements)))
+    }
+
+    /// Returns the hash of the ABI and the program of the class, which Cairo 0 hints see as the
+    /// class hash: the Starknet Keccak of {"abi": abi, "program": program} serialized by
+    /// [`python_json_dumps`], with the debug info of the program set to null.
+    ///
+    /// To keep the hashes of old classes, the program is serialized as the Python compiler of the
+    /// time did:
+    /// * Empty attributes, and the empty accessible scopes and null flow tracking data of
+    ///   attributes, are omitted.
+    /// * A missing compiler version, of classes compiled before Cairo 0.10, is omitted, and the
+    ///   Cairo types of their identifiers and references are written as "(a : felt)" rather than
+    ///   "(a: felt)".
+    ///
+    /// The ABI is serialized from `abi`, so a class whose ABI could not be parsed is hashed with a
+    /// null ABI.
+    ///
+    /// Integers are hashed as they are written, so the class must be deserialized from JSON with
+    /// the `arbitrary_precision` feature of serde_json, which this crate enables, to keep those
+    /// that do not fit in 64 bits.
+    pub fn hinted_class_hash(&self) -> StarkHash {
+        let mut program = serde_json::Map::new();
+        if let Some(attributes) = hinted_attributes(&self.program.attributes) {
+            program.insert("attributes".to_owned(), attributes);
+        }
+        program.insert("builtins".to_owned(), self.program.builtins.clone());
+        program.insert("data".to_owned(), self.program.data.clone());
+        program.insert("debug_info".to_owned(), Value::Null);
+        program.insert("hints".to_owned(), self.program.hints.clone());
+        program.insert("main_scope".to_owned(), self.program.main_scope.clone());
+        program.insert("prime".to_owned(), self.program.prime.clone());
+
+        let mut identifiers = self.program.identifiers.clone();
+        let mut reference_manager = self.program.reference_manager.clone();
+        if self.program.compiler_version.is_null() {
+            add_space_before_colons_of_cairo_types(&mut identifiers);
+            add_space_before_colons_of_cairo_types(&mut reference_manager);
+        } else {
+            program.insert("compiler_version".to_owned(), self.program.compiler_version.clone());
+        }
+        program.insert("identifiers".to_owned(), identifiers);
+        program.insert("reference_manager".to_owned(), reference_manager);
+
+        let abi = serde_json::to_value(&self.abi).expect("The ABI should serialize to JSON.");
+        let hinted_class = serde_json::json!({ "abi": abi, "program": program });
+        starknet_keccak(python_json_dumps(&hinted_class).as_bytes())
+    }
+}
+
+// The attributes of a program as they are hashed, or None if they are omitted.
+fn hinted_attributes(attributes: &Value) -> Option<Value> {
+    let attributes = match attributes {
+        Value::Array(attributes) if !attributes.is_empty() => attributes,
+        Value::Null | Value::Array(_) => return None,
+        _ => return Some(attributes.clone()),
+    };
+    let attributes = attributes
+        .iter()
+        .map(|attribute| {
+            let mut attribute = attribute.clone();
+            if let Value::Object(fields) = &mut attribute {
+                if matches!(fields.get("accessible_scopes"), Some(Value::Array(scopes)) if scopes.is_empty())
+                {
+                    fields.remove("accessible_scopes");
+                }
+                if matches!(fields.get("flow_tracking_data"), Some(Value::Null)) {
+                    fields.remove("flow_tracking_data");
+                }
+            }
+            attribute
+        })
+        .collect();
+    Some(Value::Array(attributes))
+}
+
+// Rewrites the Cairo types under the keys "cairo_type" and "value" from "(a: felt)" to
+// "(a : felt)".
+fn add_space_before_colons_of_cairo_types(value: &mut Value) {
+    match value {
+        Value::Array(values) => values.iter_mut().for_each(add_space_before_colons_of_cairo_types),
+        Value::Object(fields) => {
+            for (key, value) in fields.iter_mut() {
+                match value {
+                    Value::String(cairo_type) if key == "cairo_type" || key == "value" => {
+                        // A colon that already has a space before it should keep a single one.
+                        *cairo_type = cairo_type.replace(": ", " : ").replace("  :", " :");
+                    }
+                    _ => add_space_before_colons_of_cairo_types(value),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn program_array<'a>(value: &'a Value, field: &str) -> Result<&'a Vec<Value>, StarknetApiError> {
+    value.as_array().ok_or_else(|| invalid_program(format!("{field} is not an array")))
+}
+
+fn invalid_program(reason: String) -> StarknetApiError {
+    StarknetApiError::InvalidDeprecatedProgram(reason)
+}
+
 /// A [ContractClass](`crate::deprecated_contract_class::ContractClass`) abi entry.
 #[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
@@ -155,6 +315,12 @@ impl TryFrom<String> for EntryPointOffset {
     }
 }
 
+impl From<EntryPointOffset> for StarkFelt {
+    fn from(offset: EntryPointOffset) -> Self {
+        Self::from(u64::try_from(offset.0).expect("Expect an offset that fits in 64 bits."))
+    }
+}
+
 pub fn number_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
     let usize_value = match Value::deserialize(deserializer)? {
         Value::Number(number) => {
@@ -0,0 +1,175 @@
+use assert_matches::assert_matches;
+use serde_json::json;
+
+use crate::core::ClassHash;
+use crate::deprecated_contract_class::ContractClass;
+use crate::hash::{
+    Pedersen, Poseidon, StarkFelt, ascii_as_felt, pedersen_hash_array, starknet_keccak,
+};
+use crate::{StarknetApiError, stark_felt};
+
+const PRIME: &str = "0x800000000000011000000000000000000000000000000000000000000000001";
+
+// A class compiled before Cairo 0.10, with no compiler version.
+fn get_legacy_class() -> ContractClass {
+    serde_json::from_value(json!({
+        "abi": [{
+            "type": "function",
+            "name": "get",
+            "inputs": [],
+            "outputs": [{"name": "res", "type": "felt"}],
+            "stateMutability": "view",
+        }],
+        "entry_points_by_type": {
+            "CONSTRUCTOR": [],
+            "EXTERNAL": [{"selector": "0x1", "offset": "0x3"}, {"selector": "0x2", "offset": "0x5"}],
+            "L1_HANDLER": [],
+        },
+        "program": {
+            "attributes": [{
+                "accessible_scopes": [],
+                "end_pc": 5,
+                "flow_tracking_data": null,
+                "name": "error_message",
+                "start_pc": 3,
+                "value": "x: y",
+            }],
+            "builtins": ["pedersen", "range_check"],
+            "data": ["0x40780017fff7fff", "0x1"],
+            "debug_info": {"file_contents": {}, "instruction_locations": {}},
+            "hints": {},
+            "identifiers": {
+                "__main__.S": {
+                    "type": "struct",
+                    "members": {"t": {"cairo_type": "(a: felt, b : felt)", "offset": 0}},
+                },
+            },
+            "main_scope": "__main__",
+            "prime": PRIME,
+            "reference_manager": {"references": [{"pc": 0, "value": "[cast(fp, (a: felt)*)]"}]},
+        },
+    }))
+    .unwrap()
+}
+
+#[test]
+fn legacy_class_hash() {
+    let class = get_legacy_class();
+
+    // The output of Python's json.dumps(..., sort_keys=True) on the expected hinted class.
+    let expected_hinted_json = r#"{"abi": [{"inputs": [], "name": "get", "outputs": [{"name": "res", "type": "felt"}], "stateMutability": "view", "type": "function"}], "program": {"attributes": [{"end_pc": 5, "name": "error_message", "start_pc": 3, "value": "x: y"}], "builtins": ["pedersen", "range_check"], "data": ["0x40780017fff7fff", "0x1"], "debug_info": null, "hints": {}, "identifiers": {"__main__.S": {"members": {"t": {"cairo_type": "(a : felt, b : felt)", "offset": 0}}, "type": "struct"}}, "main_scope": "__main__", "prime": "0x800000000000011000000000000000000000000000000000000000000000001", "reference_manager": {"references": [{"pc": 0, "value": "[cast(fp, (a : felt)*)]"}]}}}"#;
+    let hinted_class_hash = starknet_keccak(expected_hinted_json.as_bytes());
+    assert_eq!(class.hinted_class_hash(), hinted_class_hash);
+
+    let expected = pedersen_hash_array(&[
+        StarkFelt::ZERO,
+        pedersen_hash_array(&[
+            stark_felt!(1_u8),
+            stark_felt!(3_u8),
+            stark_felt!(2_u8),
+            stark_felt!(5_u8),
+        ]),
+        pedersen_hash_array(&[]),
+        pedersen_hash_array(&[]),
+        pedersen_hash_array(&[
+            ascii_as_felt("pedersen").unwrap(),
+            ascii_as_felt("range_check").unwrap(),
+        ]),
+        hinted_class_hash,
+        pedersen_hash_array(&[stark_felt!("0x40780017fff7fff"), stark_felt!(1_u8)]),
+    ]);
+    assert_eq!(class.class_hash().unwrap(), ClassHash(expected));
+    assert_eq!(class.class_hash_with_hasher::<Pedersen>().unwrap(), ClassHash(expected));
+    assert_ne!(class.class_hash_with_hasher::<Poseidon>().unwrap(), ClassHash(expected));
+}
+
+#[test]
+fn hinted_class_hash_with_compiler_version() {
+    let class: ContractClass = serde_json::from_value(json!({
+        "entry_points_by_type": {},
+        "program": {
+            "attributes": [],
+            "builtins": [],
+            "compiler_version": "0.10.3",
+            "data": ["0x1"],
+            "debug_info": null,
+            "hints": {},
+            "identifiers": {"__main__.S": {"cairo_type": "(a: felt)", "type": "type_definition"}},
+            "main_scope": "__main__",
+            "prime": PRIME,
+            "reference_manager": {"references": []},
+        },
+    }))
+    .unwrap();
+
+    // The output of Python's json.dumps(..., sort_keys=True) on the expected hinted class.
+    let expected_hinted_json = r#"{"abi": null, "program": {"builtins": [], "compiler_version": "0.10.3", "data": ["0x1"], "debug_info": null, "hints": {}, "identifiers": {"__main__.S": {"cairo_type": "(a: felt)", "type": "type_definition"}}, "main_scope": "__main__", "prime": "0x800000000000011000000000000000000000000000000000000000000000001", "reference_manager": {"references": []}}}"#;
+    assert_eq!(class.hinted_class_hash(), starknet_keccak(expected_hinted_json.as_bytes()));
+}
+
+#[test]
+fn class_hash_invalid_program() {
+    let mut class = get_legacy_class();
+    class.program.data = json!([1]);
+    assert_matches!(class.class_hash(), Err(StarknetApiError::InvalidDeprecatedProgram(_)));
+}
+
+#[test]
+fn hinted_class_hash_of_large_integers() {
+    let class_json = r#"{
+        "abi": [
+            {"type": "function", "name": "get", "inputs": [], "outputs": [], "stateMutability": "view"},
+            {"type": "event", "name": "e", "keys": [], "data": [{"name": "x", "type": "felt"}]}
+        ],
+        "entry_points_by_type": {"CONSTRUCTOR": [], "EXTERNAL": [], "L1_HANDLER": []},
+        "program": {
+            "attributes": [],
+            "builtins": [],
+            "compiler_version": "0.10.3",
+            "data": ["0x1"],
+            "debug_info": null,
+            "hints": {},
+            "identifiers": {
+                "__main__.ALL_ONES": {
+                    "type": "const",
+                    "value": 340282366920938463463374607431768211455
+                },
+                "__main__.MINUS_BIG": {
+                    "type": "const",
+                    "value": -106710729501573572985208420194530329073740042555888586719489
+                },
+                "starkware.cairo.common.math.assert_le_felt.PRIME_OVER_3_HIGH": {
+                    "type": "const",
+                    "value": 3544607988759775765608368578435044694
+                }
+            },
+            "main_scope": "__main__",
+            "prime": "0x800000000000011000000000000000000000000000000000000000000000001",
+            "reference_manager": {"references": []}
+        }
+    }"#;
+    let class: ContractClass = serde_json::from_str(class_json).unwrap();
+
+    // The output of Python's json.dumps(..., sort_keys=True) on the expected hinted class.
+    let expected_hinted_json = r#"{"abi": [{"inputs": [], "name": "get", "outputs": [], "stateMutability": "view", "type": "function"}, {"data": [{"name": "x", "type": "felt"}], "keys": [], "name": "e", "type": "event"}], "program": {"builtins": [], "compiler_version": "0.10.3", "data": ["0x1"], "debug_info": null, "hints": {}, "identifiers": {"__main__.ALL_ONES": {"type": "const", "value": 340282366920938463463374607431768211455}, "__main__.MINUS_BIG": {"type": "const", "value": -106710729501573572985208420194530329073740042555888586719489}, "starkware.cairo.common.math.assert_le_felt.PRIME_OVER_3_HIGH": {"type": "const", "value": 3544607988759775765608368578435044694}}, "main_scope": "__main__", "prime": "0x800000000000011000000000000000000000000000000000000000000000001", "reference_manager": {"references": []}}}"#;
+    let hinted_class_hash = starknet_keccak(expected_hinted_json.as_bytes());
+    assert_eq!(class.hinted_class_hash(), hinted_class_hash);
+
+    // Serializing the class keeps it and its hash.
+    let serialized = serde_json::to_string(&class).unwrap();
+    let deserialized: ContractClass = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized, class);
+    assert_eq!(deserialized.hinted_class_hash(), hinted_class_hash);
+}
+
+#[test]
+fn hinted_class_hash_of_changed_abi() {
+    let mut class = get_legacy_class();
+    let hinted_class_hash = class.hinted_class_hash();
+    class.abi = None;
+    assert_ne!(class.hinted_class_hash(), hinted_class_hash);
+
+    let serialized = serde_json::to_string(&class).unwrap();
+    let deserialized: ContractClass = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized, class);
+}
@@ -4,14 +4,21 @@ mod hash_test;
 
 use std::fmt::{Debug, Display};
 use std::io::Error;
+use std::ops::{Add, Div, Mul, Neg, Sub};
 
+use primitive_types::{U256, U512};
 use serde::{Deserialize, Serialize};
-use starknet_crypto::{pedersen_hash as starknet_crypto_pedersen_hash, FieldElement};
+use sha3::{Digest, Keccak256};
+use starknet_crypto::{
+    FieldElement, pedersen_hash as starknet_crypto_pedersen_hash,
+    poseidon_hash as starknet_crypto_poseidon_hash,
+    poseidon_hash_many as starknet_crypto_poseidon_hash_many, poseidon_permute_comp,
+};
 
 use crate::serde_utils::{
-    bytes_from_hex_str, hex_str_from_bytes, BytesAsHex, NonPrefixedBytesAsHex, PrefixedBytesAsHex,
+    BytesAsHex, NonPrefixedBytesAsHex, PrefixedBytesAsHex, bytes_from_hex_str, hex_str_from_bytes,
 };
-use crate::{impl_from_through_intermediate, StarknetApiError};
+use crate::{StarknetApiError, impl_from_through_intermediate};
 
 /// Genesis state hash.
 pub const GENESIS_HASH: &str = "0x0";
@@ -20,8 +27,18 @@ pub const GENESIS_HASH: &str = "0x0";
 const CHOOSER_FULL: u8 = 15;
 const CHOOSER_HALF: u8 = 14;
 
+/// The prime of the StarkNet field, P = 2^251 + 17 * 2^192 + 1.
+pub const STARK_PRIME: U256 = U256([1, 0, 0, 0x0800_0000_0000_0011]);
+
+// P - 1 = 2^TWO_ADICITY * ODD_FACTOR_OF_PRIME_MINUS_ONE.
+const TWO_ADICITY: usize = 192;
+const ODD_FACTOR_OF_PRIME_MINUS_ONE: U256 = U256([0x0800_0000_0000_0011, 0, 0, 0]);
+// A generator of the multiplicative group, hence a quadratic non-residue.
+const MULTIPLICATIVE_GENERATOR: u8 = 3;
+
 /// An alias for [`StarkFelt`].
-/// The output of the [Pedersen hash](https://docs.starknet.io/documentation/architecture_and_concepts/Hashing/hash-functions/#pedersen_hash).
+/// The output of the [Pedersen hash](https://docs.starknet.io/documentation/architecture_and_concepts/Hashing/hash-functions/#pedersen_hash)
+/// and of the [Poseidon hash](https://docs.starknet.io/documentation/architecture_and_concepts/Hashing/hash-functions/#poseidon_hash).
 pub type StarkHash = StarkFelt;
 
 /// Computes Pedersen hash using STARK curve on two elements, as defined
@@ -43,6 +60,143 @@ pub fn pedersen_hash_array(felts: &[StarkFelt]) -> StarkHash {
     pedersen_hash(&current_hash, &data_len)
 }
 
+/// Computes Poseidon hash on two elements, as defined
+/// in <https://docs.starknet.io/documentation/architecture_and_concepts/Hashing/hash-functions/#poseidon_hash.>
+pub fn poseidon_hash(felt0: &StarkFelt, felt1: &StarkFelt) -> StarkHash {
+    StarkFelt::from(starknet_crypto_poseidon_hash(
+        FieldElement::from(*felt0),
+        FieldElement::from(*felt1),
+    ))
+}
+
+/// Computes Poseidon hash on an array of elements, as defined
+/// in <https://docs.starknet.io/documentation/architecture_and_concepts/Hashing/hash-functions/#poseidon_array_hash.>
+pub fn poseidon_hash_many(felts: &[StarkFelt]) -> StarkHash {
+    let felts: Vec<FieldElement> = felts.iter().map(|felt| FieldElement::from(*felt)).collect();
+    StarkFelt::from(starknet_crypto_poseidon_hash_many(&felts))
+}
+
+/// Applies the Hades permutation used by the Poseidon hash to a state of three elements.
+pub fn hades_permutation(state: &[StarkFelt; 3]) -> [StarkFelt; 3] {
+    let mut state = state.map(FieldElement::from);
+    poseidon_permute_comp(&mut state);
+    state.map(StarkFelt::from)
+}
+
+/// Encodes an ASCII string of at most 31 characters as a [`StarkFelt`], e.g. "invoke" or a
+/// [ChainId](`crate::core::ChainId`).
+pub fn ascii_as_felt(ascii_str: &str) -> Result<StarkFelt, StarknetApiError> {
+    StarkFelt::try_from(format!("0x{}", hex::encode(ascii_str)).as_str())
+}
+
+// Creates a lazily initialized [`StarkFelt`] from an ASCII string literal, for constants such as
+// the prefixes of hashes.
+macro_rules! lazy_ascii_as_felt {
+    ($s:literal) => {
+        once_cell::sync::Lazy::new(|| {
+            $crate::hash::ascii_as_felt($s).expect(concat!("ascii_as_felt failed for '", $s, "'"))
+        })
+    };
+}
+pub(crate) use lazy_ascii_as_felt;
+
+/// A sequence of elements to be hashed together.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct HashChain {
+    elements: Vec<StarkFelt>,
+}
+
+impl HashChain {
+    pub fn new() -> HashChain {
+        HashChain::default()
+    }
+
+    /// Appends a single element to the chain.
+    pub fn chain(mut self, felt: &StarkFelt) -> Self {
+        self.elements.push(*felt);
+        self
+    }
+
+    /// Appends all the elements of an iterator to the chain.
+    pub fn chain_iter<'a>(mut self, felts: impl Iterator<Item = &'a StarkFelt>) -> Self {
+        self.elements.extend(felts);
+        self
+    }
+
+    /// Appends the number of elements followed by the elements themselves.
+    pub fn chain_size_and_elements(self, felts: &[StarkFelt]) -> Self {
+        self.chain(&felts.len().into()).chain_iter(felts.iter())
+    }
+
+    /// Returns the [`pedersen_hash_array`] of the chain.
+    pub fn get_pedersen_hash(&self) -> StarkHash {
+        pedersen_hash_array(&self.elements)
+    }
+
+    /// Returns the [`poseidon_hash_many`] of the chain.
+    pub fn get_poseidon_hash(&self) -> StarkHash {
+        poseidon_hash_many(&self.elements)
+    }
+
+    /// Returns the hash of the chain by the given [`StarkHasher`].
+    pub fn get_hash<H: StarkHasher>(&self) -> StarkHash {
+        H::hash_array(&self.elements)
+    }
+}
+
+/// A hash function over field elements, e.g. for computing addresses and commitments.
+///
+/// Patricia tries are generic over the hash, and calculations that use a single hash have a
+/// `_with_hasher` variant, e.g. [`calculate_contract_address_with_hasher`]. Block hashes,
+/// transaction hashes and the transaction and event commitments use the Pedersen or the Poseidon
+/// hash by the Starknet version of their block, and are not generic.
+///
+/// [`calculate_contract_address_with_hasher`]: crate::core::calculate_contract_address_with_hasher
+pub trait StarkHasher {
+    /// Computes the hash of two elements.
+    fn hash(felt0: &StarkFelt, felt1: &StarkFelt) -> StarkHash;
+
+    /// Computes the hash of an array of elements.
+    fn hash_array(felts: &[StarkFelt]) -> StarkHash;
+}
+
+/// The [`StarkHasher`] of [`pedersen_hash`] and [`pedersen_hash_array`].
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Pedersen;
+
+impl StarkHasher for Pedersen {
+    fn hash(felt0: &StarkFelt, felt1: &StarkFelt) -> StarkHash {
+        pedersen_hash(felt0, felt1)
+    }
+
+    fn hash_array(felts: &[StarkFelt]) -> StarkHash {
+        pedersen_hash_array(felts)
+    }
+}
+
+/// The [`StarkHasher`] of [`poseidon_hash`] and [`poseidon_hash_many`].
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Poseidon;
+
+impl StarkHasher for Poseidon {
+    fn hash(felt0: &StarkFelt, felt1: &StarkFelt) -> StarkHash {
+        poseidon_hash(felt0, felt1)
+    }
+
+    fn hash_array(felts: &[StarkFelt]) -> StarkHash {
+        poseidon_hash_many(felts)
+    }
+}
+
+/// Computes the Keccak256 hash of `data`, truncated to its 250 least significant bits, as defined
+/// in <https://docs.starknet.io/documentation/architecture_and_concepts/Hashing/hash-functions/#starknet_keccak.>
+pub fn starknet_keccak(data: &[u8]) -> StarkHash {
+    let mut bytes: [u8; 32] = Keccak256::digest(data).into();
+    bytes[0] &= 0x03;
+    // A 250 bit value is smaller than the field prime.
+    StarkFelt::new_unchecked(bytes)
+}
+
 // TODO: Move to a different crate.
 /// The StarkNet [field element](https://docs.starknet.io/documentation/architecture_and_concepts/Hashing/hash-functions/#domain_and_range).
 #[derive(Copy, Clone, Eq, PartialEq, Default, Hash, Deserialize, Serialize, PartialOrd, Ord)]
@@ -50,13 +204,38 @@ pub fn pedersen_hash_array(felts: &[StarkFelt]) -> StarkHash {
 pub struct StarkFelt([u8; 32]);
 
 impl StarkFelt {
-    /// Returns a new [`StarkFelt`].
+    /// The zero element of the field.
+    pub const ZERO: StarkFelt = StarkFelt([0u8; 32]);
+    /// The unit element of the field.
+    pub const ONE: StarkFelt = StarkFelt([
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 1,
+    ]);
+
+    /// Returns a new [`StarkFelt`], if the big-endian `bytes` represent a value smaller than
+    /// [`STARK_PRIME`].
     pub fn new(bytes: [u8; 32]) -> Result<StarkFelt, StarknetApiError> {
-        // msb nibble must be 0. This is not a tight bound.
-        if bytes[0] < 0x10 {
+        if U256::from_big_endian(&bytes) < STARK_PRIME {
             return Ok(Self(bytes));
         }
-        Err(StarknetApiError::OutOfRange { string: hex_str_from_bytes::<32, true>(bytes) })
+        Err(StarknetApiError::OutOfRange {
+            string: format!(
+                "{} is not in [0x0, {STARK_PRIME:#x})",
+                hex_str_from_bytes::<32, true>(bytes)
+            ),
+        })
+    }
+
+    /// Returns a new [`StarkFelt`] without checking that `bytes` are in range.
+    /// Should only be used for values that were already validated, e.g. when reading from storage.
+    pub const fn new_unchecked(bytes: [u8; 32]) -> StarkFelt {
+        Self(bytes)
+    }
+
+    /// Returns whether the felt is in the field, which only a felt created by
+    /// [`StarkFelt::new_unchecked`] may not be.
+    pub fn is_in_field(&self) -> bool {
+        U256::from_big_endian(&self.0) < STARK_PRIME
     }
 
     /// Storage efficient serialization for field elements.
@@ -125,6 +304,76 @@ impl StarkFelt {
         &self.0
     }
 
+    /// Returns the multiplicative inverse of the element, or `None` for zero.
+    pub fn inverse(&self) -> Option<StarkFelt> {
+        let value = self.as_field_value();
+        if value.is_zero() {
+            return None;
+        }
+        // By Fermat's little theorem, a^(P - 2) = a^-1 (mod P).
+        Some(Self::from_field_value(mod_pow(value, STARK_PRIME - 2, STARK_PRIME)))
+    }
+
+    /// Raises the element to the power of `exponent`, taken as an integer.
+    pub fn pow(&self, exponent: impl Into<StarkFelt>) -> StarkFelt {
+        let exponent = U256::from_big_endian(&exponent.into().0);
+        Self::from_field_value(mod_pow(self.as_field_value(), exponent, STARK_PRIME))
+    }
+
+    /// Returns a square root of the element, or `None` if it is not a quadratic residue.
+    /// Out of the two roots, r and P - r, the smaller one is returned.
+    pub fn sqrt(&self) -> Option<StarkFelt> {
+        let value = self.as_field_value();
+        if value.is_zero() {
+            return Some(Self::ZERO);
+        }
+        // Euler's criterion.
+        if mod_pow(value, (STARK_PRIME - 1) >> 1, STARK_PRIME) != U256::one() {
+            return None;
+        }
+
+        // Tonelli-Shanks.
+        let mut max_order = TWO_ADICITY;
+        let mut root_of_unity = mod_pow(
+            U256::from(MULTIPLICATIVE_GENERATOR),
+            ODD_FACTOR_OF_PRIME_MINUS_ONE,
+            STARK_PRIME,
+        );
+        let mut root = mod_pow(value, (ODD_FACTOR_OF_PRIME_MINUS_ONE + 1) >> 1, STARK_PRIME);
+        let mut error = mod_pow(value, ODD_FACTOR_OF_PRIME_MINUS_ONE, STARK_PRIME);
+        while error != U256::one() {
+            // The least order such that error^(2^order) = 1; it is smaller than max_order.
+            let mut order = 0;
+            let mut power = error;
+            while power != U256::one() {
+                power = mod_mul(power, power, STARK_PRIME);
+                order += 1;
+            }
+            let mut correction = root_of_unity;
+            for _ in 0..max_order - order - 1 {
+                correction = mod_mul(correction, correction, STARK_PRIME);
+            }
+            root = mod_mul(root, correction, STARK_PRIME);
+            root_of_unity = mod_mul(correction, correction, STARK_PRIME);
+            error = mod_mul(error, root_of_unity, STARK_PRIME);
+            max_order = order;
+        }
+
+        let negated_root = STARK_PRIME - root;
+        Some(Self::from_field_value(std::cmp::min(root, negated_root)))
+    }
+
+    // The element as an integer in [0, P).
+    fn as_field_value(&self) -> U256 {
+        U256::from_big_endian(&self.0) % STARK_PRIME
+    }
+
+    fn from_field_value(value: U256) -> Self {
+        let mut bytes = [0u8; 32];
+        value.to_big_endian(&mut bytes);
+        Self(bytes)
+    }
+
     fn str_format(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = format!("0x{}", hex::encode(self.0));
         f.debug_tuple("StarkFelt").field(&s).finish()
@@ -157,6 +406,17 @@ impl From<u128> for StarkFelt {
 
 impl_from_through_intermediate!(u128, StarkFelt, u8, u16, u32, u64);
 
+impl From<usize> for StarkFelt {
+    fn from(val: usize) -> Self {
+        const COMPLIMENT_OF_USIZE: usize =
+            std::mem::size_of::<StarkFelt>() - std::mem::size_of::<usize>();
+
+        let mut bytes = [0u8; 32];
+        bytes[COMPLIMENT_OF_USIZE..].copy_from_slice(&val.to_be_bytes());
+        Self(bytes)
+    }
+}
+
 impl From<FieldElement> for StarkFelt {
     fn from(fe: FieldElement) -> Self {
         // Should not fail.
@@ -166,7 +426,7 @@ impl From<FieldElement> for StarkFelt {
 
 impl From<StarkFelt> for FieldElement {
     fn from(felt: StarkFelt) -> Self {
-        // Should not fail.
+        // Should not fail, unless the felt was created unchecked from an out of range value.
         Self::from_bytes_be(&felt.0).expect("Convert StarkFelf to FieldElement.")
     }
 }
@@ -205,7 +465,6 @@ impl TryFrom<StarkFelt> for usize {
     }
 }
 
-// TODO(Arni, 1/1/2024): This is a Hack. Remove this and implement arethmetics for StarkFelt.
 impl TryFrom<StarkFelt> for u64 {
     type Error = StarknetApiError;
     fn try_from(felt: StarkFelt) -> Result<Self, Self::Error> {
@@ -220,6 +479,80 @@ impl TryFrom<StarkFelt> for u64 {
     }
 }
 
+// Modular arithmetic over integers in [0, modulus), for a modulus smaller than 2^255.
+pub(crate) fn mod_add(a: U256, b: U256, modulus: U256) -> U256 {
+    // Both operands are smaller than 2^255, so the sum does not overflow.
+    let sum = a + b;
+    if sum >= modulus { sum - modulus } else { sum }
+}
+
+pub(crate) fn mod_sub(a: U256, b: U256, modulus: U256) -> U256 {
+    if a >= b { a - b } else { modulus - (b - a) }
+}
+
+pub(crate) fn mod_mul(a: U256, b: U256, modulus: U256) -> U256 {
+    let product = a.full_mul(b) % U512::from(modulus);
+    U256::try_from(product)
+        .expect("A value reduced modulo a 256 bit modulus should fit in 256 bits.")
+}
+
+pub(crate) fn mod_pow(base: U256, exponent: U256, modulus: U256) -> U256 {
+    let mut result = U256::one();
+    for i in (0..exponent.bits()).rev() {
+        result = mod_mul(result, result, modulus);
+        if exponent.bit(i) {
+            result = mod_mul(result, base, modulus);
+        }
+    }
+    result
+}
+
+impl Add for StarkFelt {
+    type Output = StarkFelt;
+
+    fn add(self, rhs: StarkFelt) -> StarkFelt {
+        Self::from_field_value(mod_add(self.as_field_value(), rhs.as_field_value(), STARK_PRIME))
+    }
+}
+
+impl Sub for StarkFelt {
+    type Output = StarkFelt;
+
+    fn sub(self, rhs: StarkFelt) -> StarkFelt {
+        Self::from_field_value(mod_sub(self.as_field_value(), rhs.as_field_value(), STARK_PRIME))
+    }
+}
+
+impl Mul for StarkFelt {
+    type Output = StarkFelt;
+
+    fn mul(self, rhs: StarkFelt) -> StarkFelt {
+        Self::from_field_value(mod_mul(self.as_field_value(), rhs.as_field_value(), STARK_PRIME))
+    }
+}
+
+impl Neg for StarkFelt {
+    type Output = StarkFelt;
+
+    fn neg(self) -> StarkFelt {
+        Self::from_field_value(mod_sub(U256::zero(), self.as_field_value(), STARK_PRIME))
+    }
+}
+
+impl Div for StarkFelt {
+    type Output = StarkFelt;
+
+    /// Panics if `rhs` is zero.
+    fn div(self, rhs: StarkFelt) -> StarkFelt {
+        let rhs_inverse = rhs.inverse().expect("Division by zero.");
+        Self::from_field_value(mod_mul(
+            self.as_field_value(),
+            rhs_inverse.as_field_value(),
+            STARK_PRIME,
+        ))
+    }
+}
+
 impl Debug for StarkFelt {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.str_format(f)
@@ -1,8 +1,12 @@
 use assert_matches::assert_matches;
+use starknet_crypto::FieldElement;
 
-use crate::hash::{pedersen_hash, pedersen_hash_array, StarkFelt};
+use crate::hash::{
+    StarkFelt, hades_permutation, pedersen_hash, pedersen_hash_array, poseidon_hash,
+    poseidon_hash_many, starknet_keccak,
+};
 use crate::transaction::Fee;
-use crate::{stark_felt, StarknetApiError};
+use crate::{StarknetApiError, stark_felt};
 
 #[test]
 fn pedersen_hash_correctness() {
@@ -26,6 +30,68 @@ fn pedersen_hash_array_correctness() {
     assert_eq!(pedersen_hash_array(&[a, b, c]), expected);
 }
 
+#[test]
+fn poseidon_hash_correctness() {
+    // Test vectors generated from cairo-lang v0.11.0.
+    let a = stark_felt!("0xb662f9017fa7956fd70e26129b1833e10ad000fd37b4d9f4e0ce6884b7bbe");
+    let b = stark_felt!("0x1fe356bf76102cdae1bfbdc173602ead228b12904c00dad9cf16e035468bea");
+    let expected = stark_felt!("0x75540825a6ecc5dc7d7c2f5f868164182742227f1367d66c43ee51ec7937a81");
+    assert_eq!(poseidon_hash(&a, &b), expected);
+
+    let a = stark_felt!("0xf4e01b2032298f86b539e3d3ac05ced20d2ef275273f9325f8827717156529");
+    let b = stark_felt!("0x587bc46f5f58e0511b93c31134652a689d761a9e7f234f0f130c52e4679f3a");
+    let expected = stark_felt!("0xbdb3180fdcfd6d6f172beb401af54dd71b6569e6061767234db2b777adf98b");
+    assert_eq!(poseidon_hash(&a, &b), expected);
+}
+
+#[test]
+fn poseidon_hash_many_correctness() {
+    // Test vectors generated from cairo-lang v0.11.0.
+    let felts = [
+        stark_felt!("0x9bf52404586087391c5fbb42538692e7ca2149bac13c145ae4230a51a6fc47"),
+        stark_felt!("0x40304159ee9d2d611120fbd7c7fb8020cc8f7a599bfa108e0e085222b862c0"),
+        stark_felt!("0x46286e4f3c450761d960d6a151a9c0988f9e16f8a48d4c0a85817c009f806a"),
+    ];
+    let expected = stark_felt!("0x1ec38b38dc88bac7b0ed6ff6326f975a06a59ac601b417745fd412a5d38e4f7");
+    assert_eq!(poseidon_hash_many(&felts), expected);
+
+    let felts = [
+        stark_felt!("0xbdace8883922662601b2fd197bb660b081fcf383ede60725bd080d4b5f2fd3"),
+        stark_felt!("0x1eb1daaf3fdad326b959dec70ced23649cdf8786537cee0c5758a1a4229097"),
+        stark_felt!("0x869ca04071b779d6f940cdf33e62d51521e19223ab148ef571856ff3a44ff1"),
+        stark_felt!("0x533e6df8d7c4b634b1f27035c8676a7439c635e1fea356484de7f0de677930"),
+    ];
+    let expected = stark_felt!("0x2520b8f910174c3e650725baacad4efafaae7623c69a0b5513d75e500f36624");
+    assert_eq!(poseidon_hash_many(&felts), expected);
+}
+
+#[test]
+fn hades_permutation_correctness() {
+    // Poseidon hash of two elements is the first element of the permuted state [x, y, 2].
+    let a = stark_felt!("0xaa");
+    let b = stark_felt!("0xbb");
+    assert_eq!(hades_permutation(&[a, b, stark_felt!("0x2")])[0], poseidon_hash(&a, &b));
+
+    // Poseidon hash of a single element array is the first element of the padded state [x, 1, 0].
+    assert_eq!(
+        hades_permutation(&[a, StarkFelt::ONE, StarkFelt::ZERO])[0],
+        poseidon_hash_many(&[a])
+    );
+}
+
+#[test]
+fn starknet_keccak_correctness() {
+    assert_eq!(
+        starknet_keccak(b"transfer"),
+        stark_felt!("0x83afd3f4caedc6eebf44246fe54e38c95e3179a5ec9ea81740eca5b482d12e")
+    );
+    // The keccak256 of the empty string is 0xc5d2...a470; only its 250 lower bits are kept.
+    assert_eq!(
+        starknet_keccak(b""),
+        stark_felt!("0x1d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470")
+    );
+}
+
 #[test]
 fn hash_macro() {
     assert_eq!(
@@ -38,6 +104,24 @@ fn hash_macro() {
     );
 }
 
+#[test]
+fn felt_out_of_range() {
+    const PRIME: &str = "0x800000000000011000000000000000000000000000000000000000000000001";
+
+    // P - 1 is the largest field element.
+    let max =
+        StarkFelt::try_from("0x800000000000011000000000000000000000000000000000000000000000000");
+    assert_eq!(max.unwrap(), StarkFelt::from(FieldElement::MAX));
+
+    let err = StarkFelt::try_from(PRIME).unwrap_err();
+    assert_matches!(err, StarknetApiError::OutOfRange { string } if string.starts_with(PRIME));
+    assert_matches!(
+        StarkFelt::try_from("0xfff0000000000000000000000000000000000000000000000000000000000000"),
+        Err(StarknetApiError::OutOfRange { .. })
+    );
+    assert!(serde_json::from_str::<StarkFelt>(&format!("\"{PRIME}\"")).is_err());
+}
+
 #[test]
 fn hash_json_serde() {
     let hash = stark_felt!("0x123");
@@ -61,7 +145,8 @@ fn hash_serde() {
         for i in 0..n_nibbles {
             bytes[31 - (i >> 1)] |= 15 << (4 * (i & 1));
         }
-        let h = StarkFelt::new(bytes).unwrap();
+        // The storage encoding also supports values that are not smaller than the field prime.
+        let h = StarkFelt::new_unchecked(bytes);
         let mut res = Vec::new();
         assert!(h.serialize(&mut res).is_ok());
         assert_eq!(res.len(), enc_len(n_nibbles));
@@ -91,3 +176,76 @@ fn felt_to_u64_and_back() {
     let err = u64::try_from(another_felt).unwrap_err();
     assert_matches!(err, StarknetApiError::OutOfRange { .. });
 }
+
+#[test]
+fn felt_from_usize_and_back() {
+    let value = usize::MAX;
+    let felt: StarkFelt = value.into();
+    assert_eq!(felt, StarkFelt::from(value as u128));
+    assert_eq!(usize::try_from(felt).unwrap(), value);
+}
+
+#[test]
+fn felt_arithmetic_matches_field_element() {
+    let values = [
+        stark_felt!("0x0"),
+        stark_felt!("0x1"),
+        stark_felt!("0x1234567890abcdef"),
+        stark_felt!("0x03d937c035c878245caf64531a5756109c53068da139362728feb561405371cb"),
+        stark_felt!("0x0208a0a10250e382e1e4bbe2880906c2791bf6275695e02fbbc6aeff9cd8b31a"),
+        StarkFelt::from(FieldElement::MAX),
+    ];
+    for a in values {
+        assert_eq!(FieldElement::from(-a), -FieldElement::from(a));
+        for b in values {
+            let (fa, fb) = (FieldElement::from(a), FieldElement::from(b));
+            assert_eq!(FieldElement::from(a + b), fa + fb);
+            assert_eq!(FieldElement::from(a - b), fa - fb);
+            assert_eq!(FieldElement::from(a * b), fa * fb);
+            if b != StarkFelt::ZERO {
+                assert_eq!(FieldElement::from(a / b), fa * fb.invert().unwrap());
+            }
+        }
+    }
+}
+
+#[test]
+fn felt_inverse() {
+    assert_eq!(StarkFelt::ZERO.inverse(), None);
+    let a = stark_felt!("0x1234567890abcdef");
+    assert_eq!(a * a.inverse().unwrap(), StarkFelt::ONE);
+    assert_eq!(
+        StarkFelt::from(FieldElement::MAX).inverse(),
+        Some(StarkFelt::from(FieldElement::MAX))
+    );
+}
+
+#[test]
+#[should_panic(expected = "Division by zero.")]
+fn felt_division_by_zero() {
+    let _ = StarkFelt::ONE / StarkFelt::ZERO;
+}
+
+#[test]
+fn felt_pow() {
+    let a = stark_felt!("0x3");
+    assert_eq!(a.pow(0_u8), StarkFelt::ONE);
+    assert_eq!(a.pow(5_u8), stark_felt!("0xf3"));
+    // a^(P - 1) = 1.
+    assert_eq!(a.pow(FieldElement::MAX), StarkFelt::ONE);
+}
+
+#[test]
+fn felt_sqrt() {
+    assert_eq!(StarkFelt::ZERO.sqrt(), Some(StarkFelt::ZERO));
+    assert_eq!(stark_felt!("0x19").sqrt(), Some(stark_felt!("0x5")));
+    assert_eq!((-stark_felt!("0x5") * -stark_felt!("0x5")).sqrt(), Some(stark_felt!("0x5")));
+
+    let a = stark_felt!("0x03d937c035c878245caf64531a5756109c53068da139362728feb561405371cb");
+    let root = (a * a).sqrt().unwrap();
+    assert!(root == a || root == -a);
+    assert!(root <= -root);
+
+    // The multiplicative generator is a quadratic non-residue.
+    assert_eq!(stark_felt!("0x3").sqrt(), None);
+}
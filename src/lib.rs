@@ -3,17 +3,33 @@
 //! [`Starknet`]: https://starknet.io/
 
 pub mod block;
+pub mod block_commitment;
+pub mod compiled_class;
 pub mod core;
+pub mod data_availability;
 pub mod deprecated_contract_class;
 pub mod hash;
+pub mod patricia;
 pub mod serde_utils;
 pub mod state;
+pub mod state_commitment;
+pub mod state_reader;
+#[cfg(test)]
+mod test_utils;
 pub mod transaction;
+pub mod transaction_hash;
 pub mod type_utils;
+pub mod versioned_state;
 
 use std::num::ParseIntError;
 
+use block::{BlockHash, BlockNumber, StarknetVersion};
+use compiled_class::SierraVersion;
+use hash::StarkHash;
 use serde_utils::InnerDeserializationError;
+use state::{StateDiffError, StateNumber};
+
+use crate::core::{ClassHash, CompiledClassHash, GlobalRoot};
 
 /// The error type returned by StarknetApi.
 #[derive(thiserror::Error, Clone, Debug)]
@@ -27,4 +43,97 @@ pub enum StarknetApiError {
     /// Error when serializing into number.
     #[error(transparent)]
     ParseIntError(#[from] ParseIntError),
+    /// A block whose hash is not the hash of its content.
+    #[error("Block {block_number} has hash {expected}, but its content hashes to {calculated}.")]
+    BlockHashMismatch { block_number: BlockNumber, expected: BlockHash, calculated: BlockHash },
+    /// A block header without a commitment that the hash of its block requires.
+    #[error("Block {block_number} has no {commitment}.")]
+    MissingBlockCommitment { block_number: BlockNumber, commitment: &'static str },
+    /// A block header with a commitment that is not the commitment of the block body.
+    #[error("Block {block_number} has {commitment} {expected}, but its body has {calculated}.")]
+    BlockCommitmentMismatch {
+        block_number: BlockNumber,
+        commitment: &'static str,
+        expected: StarkHash,
+        calculated: StarkHash,
+    },
+    /// A block header with a count that is not the count of the block body.
+    #[error("Block {block_number} has {count} {expected}, but its body has {calculated}.")]
+    BlockCountMismatch {
+        block_number: BlockNumber,
+        count: &'static str,
+        expected: usize,
+        calculated: usize,
+    },
+    /// A block body with a list whose length is not the number of its transactions.
+    #[error("Block {block_number} has {transaction_count} transactions, but {length} {field}.")]
+    BlockBodyLengthMismatch {
+        block_number: BlockNumber,
+        field: &'static str,
+        length: usize,
+        transaction_count: usize,
+    },
+    /// A block of a Starknet version whose block hash scheme is not supported.
+    #[error("Block {block_number} of Starknet {starknet_version} has an unsupported hash scheme.")]
+    UnsupportedBlockHashVersion { block_number: BlockNumber, starknet_version: StarknetVersion },
+    /// A Merkle proof that does not prove a value against its root.
+    #[error("Invalid Merkle proof: {0}.")]
+    InvalidProof(String),
+    /// A request for a state by a global root that it does not have.
+    #[error("Unknown global root {0}.")]
+    UnknownGlobalRoot(GlobalRoot),
+    /// A state whose global root is not the expected one.
+    #[error("Expected global root {expected}, but the state has root {calculated}.")]
+    GlobalRootMismatch { expected: GlobalRoot, calculated: GlobalRoot },
+    /// A state diff that breaks its invariants.
+    #[error(transparent)]
+    StateDiff(#[from] StateDiffError),
+    /// A request for a class that is not declared.
+    #[error("Class {0} is not declared.")]
+    UndeclaredClass(ClassHash),
+    /// A block that does not follow the last known block.
+    #[error("Expected block {expected}, got block {got}.")]
+    UnexpectedBlockNumber { expected: BlockNumber, got: BlockNumber },
+    /// A request for a state past the last known block.
+    #[error("The state right before block {} is not known.", .0.block_after())]
+    UnknownState(StateNumber),
+    /// State diff data posted on L1 that cannot be decoded.
+    #[error("Invalid data availability encoding: {0}.")]
+    InvalidDataAvailabilityEncoding(String),
+    /// A program of a deprecated class that is not of the expected form.
+    #[error("Invalid program of a deprecated class: {0}.")]
+    InvalidDeprecatedProgram(String),
+    /// Bytecode segment lengths that do not add up to the length of the bytecode.
+    #[error("The bytecode segment lengths do not add up to the bytecode length {0}.")]
+    BytecodeSegmentLengthsMismatch(usize),
+    /// A Cairo 1 class whose class hash scheme is not known.
+    #[error("Unsupported contract class version '{0}'.")]
+    UnsupportedContractClassVersion(String),
+    /// A class that cannot be converted to another class type.
+    #[error("Failed to convert the class: {0}.")]
+    ClassConversion(String),
+    /// A class of a Sierra version that the compiler does not support.
+    #[error("Unsupported Sierra version {version}, the latest supported version is {max_version}.")]
+    UnsupportedSierraVersion { version: SierraVersion, max_version: SierraVersion },
+    /// A Sierra program that cannot be parsed.
+    #[error("Invalid Sierra program: {0}.")]
+    InvalidSierraProgram(String),
+    /// A class that uses libfuncs that are not allowed, or an allowed libfuncs list that cannot be
+    /// loaded.
+    #[error("Allowed libfuncs check failed: {0}.")]
+    AllowedLibfuncs(String),
+    /// A Sierra class that the compiler fails to compile.
+    #[error("Failed to compile the class: {0}.")]
+    SierraCompilation(String),
+    /// A compiled class whose bytecode is larger than allowed.
+    #[error("The bytecode is larger than the maximum of {max_bytecode_size} felts.")]
+    BytecodeSizeTooLarge { max_bytecode_size: usize },
+    /// A declared class whose hash is not the declared one.
+    #[error("Expected class hash {expected}, but the class has hash {calculated}.")]
+    ClassHashMismatch { expected: ClassHash, calculated: ClassHash },
+    /// A declared class whose compiled class hash is not the declared one.
+    #[error(
+        "Expected compiled class hash {expected}, but the compiled class has hash {calculated}."
+    )]
+    CompiledClassHashMismatch { expected: CompiledClassHash, calculated: CompiledClassHash },
 }
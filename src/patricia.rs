@@ -0,0 +1,429 @@
+#[cfg(test)]
+#[path = "patricia_test.rs"]
+mod patricia_test;
+
+use std::marker::PhantomData;
+
+use once_cell::sync::OnceCell;
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+
+use crate::StarknetApiError;
+use crate::hash::{StarkFelt, StarkHash, StarkHasher};
+use crate::state::StorageKey;
+
+/// The height of the Patricia-Merkle tries of the state: contract storage, contracts and classes.
+pub const STATE_TREE_HEIGHT: u8 = 251;
+/// The height of the Patricia-Merkle trees that commit to the content of a block.
+pub const BLOCK_COMMITMENT_TREE_HEIGHT: u8 = 64;
+
+/// Calculates the root of a height-64 Patricia-Merkle tree, whose leaves are the given values at
+/// indices 0, 1, 2, ...
+pub fn calculate_root<H: StarkHasher>(values: &[StarkFelt]) -> StarkHash {
+    let mut trie = PatriciaTrie::<H>::with_height(BLOCK_COMMITMENT_TREE_HEIGHT);
+    for (index, value) in (0_u64..).zip(values.iter()) {
+        trie.insert_at(U256::from(index), *value);
+    }
+    trie.root()
+}
+
+/// A binary Merkle-Patricia trie over [`StarkHasher`](`crate::hash::StarkHasher`) `H`.
+///
+/// A binary node is hashed as H(left, right) and an edge node as H(child, path) + length. Empty
+/// subtries are not stored, so setting a key to zero deletes it, and the root of an empty trie is
+/// zero. The hashes of the nodes are cached, so after a change only the nodes on the path to the
+/// changed key are hashed again.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PatriciaTrie<H: StarkHasher> {
+    height: u8,
+    root: Option<PatriciaNode>,
+    hasher: PhantomData<H>,
+}
+
+impl<H: StarkHasher> Default for PatriciaTrie<H> {
+    fn default() -> Self {
+        Self::with_height(STATE_TREE_HEIGHT)
+    }
+}
+
+impl<H: StarkHasher> PatriciaTrie<H> {
+    /// Returns an empty height-251 trie.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn with_height(height: u8) -> Self {
+        Self { height, root: None, hasher: PhantomData }
+    }
+
+    /// Returns the top node of the trie, or None if the trie is empty.
+    pub fn root_node(&self) -> Option<&PatriciaNode> {
+        self.root.as_ref()
+    }
+
+    /// Returns the value at the key, which is zero if the key is not in the trie.
+    pub fn get(&self, key: &StorageKey) -> StarkFelt {
+        let key = key_as_u256(key);
+        let mut node = self.root.as_ref();
+        let mut height = self.height;
+        while let Some(current) = node {
+            match current {
+                PatriciaNode::Leaf(value) => return *value,
+                PatriciaNode::Binary(binary) => {
+                    height -= 1;
+                    node = Some(if key.bit(height.into()) { &binary.right } else { &binary.left });
+                }
+                PatriciaNode::Edge(edge) => {
+                    let child_height = height - edge.path.length;
+                    if key_bits(key, height) >> child_height != edge.path.as_u256() {
+                        break;
+                    }
+                    height = child_height;
+                    node = Some(&edge.child);
+                }
+            }
+        }
+        StarkFelt::ZERO
+    }
+
+    /// Sets the value at the key. A zero value deletes the key.
+    pub fn insert(&mut self, key: &StorageKey, value: StarkFelt) {
+        self.insert_at(key_as_u256(key), value);
+    }
+
+    /// Deletes the key from the trie, if present.
+    pub fn delete(&mut self, key: &StorageKey) {
+        self.delete_at(key_as_u256(key));
+    }
+
+    /// Returns the root hash of the trie.
+    pub fn root(&self) -> StarkHash {
+        self.root.as_ref().map_or(StarkFelt::ZERO, |node| node.hash::<H>())
+    }
+
+    /// Returns the nodes on the path from the root towards the key, which prove its value, or its
+    /// absence when the path leaves the key. See [`verify_proof`].
+    pub fn get_proof(&self, key: &StorageKey) -> Vec<ProofNode> {
+        let key = key_as_u256(key);
+        let mut proof = vec![];
+        let mut node = self.root.as_ref();
+        let mut height = self.height;
+        while let Some(current) = node {
+            match current {
+                PatriciaNode::Leaf(_) => break,
+                PatriciaNode::Binary(binary) => {
+                    proof.push(ProofNode::Binary {
+                        left: binary.left.hash::<H>(),
+                        right: binary.right.hash::<H>(),
+                    });
+                    height -= 1;
+                    node = Some(if key.bit(height.into()) { &binary.right } else { &binary.left });
+                }
+                PatriciaNode::Edge(edge) => {
+                    proof.push(ProofNode::Edge { child: edge.child.hash::<H>(), path: edge.path });
+                    let child_height = height - edge.path.length;
+                    if key_bits(key, height) >> child_height != edge.path.as_u256() {
+                        break;
+                    }
+                    height = child_height;
+                    node = Some(&edge.child);
+                }
+            }
+        }
+        proof
+    }
+
+    fn insert_at(&mut self, key: U256, value: StarkFelt) {
+        if value == StarkFelt::ZERO {
+            self.delete_at(key);
+        } else {
+            self.root = Some(insert_into(self.root.take(), key, self.height, value));
+        }
+    }
+
+    fn delete_at(&mut self, key: U256) {
+        self.root = self.root.take().and_then(|root| delete_from(root, key, self.height));
+    }
+}
+
+/// A node of a [`PatriciaTrie`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum PatriciaNode {
+    /// A non-zero value at the bottom of the trie.
+    Leaf(StarkFelt),
+    /// A node with two non-empty children.
+    Binary(BinaryNode),
+    /// A path down the trie, along which every node has a single non-empty child.
+    Edge(EdgeNode),
+}
+
+impl PatriciaNode {
+    /// Returns the hash of the node, which is the value itself for a leaf.
+    ///
+    /// The hash is cached in the node, so a node must always be hashed with the same hasher, as
+    /// the nodes of a [`PatriciaTrie`] are.
+    pub fn hash<H: StarkHasher>(&self) -> StarkHash {
+        match self {
+            PatriciaNode::Leaf(value) => *value,
+            PatriciaNode::Binary(binary) => *binary
+                .hash
+                .0
+                .get_or_init(|| H::hash(&binary.left.hash::<H>(), &binary.right.hash::<H>())),
+            PatriciaNode::Edge(edge) => *edge.hash.0.get_or_init(|| {
+                H::hash(&edge.child.hash::<H>(), &edge.path.value)
+                    + StarkFelt::from(u64::from(edge.path.length))
+            }),
+        }
+    }
+}
+
+/// A [`PatriciaNode`] with two non-empty children.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct BinaryNode {
+    pub left: Box<PatriciaNode>,
+    pub right: Box<PatriciaNode>,
+    hash: CachedHash,
+}
+
+/// A [`PatriciaNode`] that skips down the trie along a path, to its only non-empty descendant.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct EdgeNode {
+    pub path: EdgePath,
+    pub child: Box<PatriciaNode>,
+    hash: CachedHash,
+}
+
+// The hash of a node, calculated when it is first needed. Nodes are replaced rather than modified
+// when the trie changes, so it never goes stale. It is derived from the node, so it is ignored
+// when comparing nodes.
+#[derive(Debug, Clone, Default)]
+struct CachedHash(OnceCell<StarkHash>);
+
+impl PartialEq for CachedHash {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for CachedHash {}
+
+impl std::hash::Hash for CachedHash {
+    fn hash<S: std::hash::Hasher>(&self, _state: &mut S) {}
+}
+
+/// A node in a Merkle proof, holding the hashes of its children. Serialized as in the
+/// `starknet_getProof` API.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofNode {
+    Binary { left: StarkHash, right: StarkHash },
+    Edge { child: StarkHash, path: EdgePath },
+}
+
+impl ProofNode {
+    /// Returns the hash of the node, as in [`PatriciaNode::hash`].
+    pub fn hash<H: StarkHasher>(&self) -> StarkHash {
+        match self {
+            ProofNode::Binary { left, right } => H::hash(left, right),
+            ProofNode::Edge { child, path } => {
+                H::hash(child, &path.value) + StarkFelt::from(u64::from(path.length))
+            }
+        }
+    }
+}
+
+/// Verifies a Merkle proof of a key in a height-251 trie with the given root, as returned by
+/// [`PatriciaTrie::get_proof`], and returns the value of the key, which is zero if the proof shows
+/// the key is not in the trie.
+pub fn verify_proof<H: StarkHasher>(
+    root: &StarkHash,
+    key: &StorageKey,
+    proof: &[ProofNode],
+) -> Result<StarkFelt, StarknetApiError> {
+    let key = key_as_u256(key);
+    let mut expected_hash = *root;
+    let mut height = STATE_TREE_HEIGHT;
+    for (i, node) in proof.iter().enumerate() {
+        if height == 0 {
+            return Err(invalid_proof("it continues below the leaf"));
+        }
+        if node.hash::<H>() != expected_hash {
+            return Err(invalid_proof(&format!("node {i} does not hash to {expected_hash}")));
+        }
+        match node {
+            ProofNode::Binary { left, right } => {
+                height -= 1;
+                expected_hash = if key.bit(height.into()) { *right } else { *left };
+            }
+            ProofNode::Edge { child, path } => {
+                if path.length == 0 || path.length > height {
+                    return Err(invalid_proof(&format!("node {i} has an invalid path length")));
+                }
+                let child_height = height - path.length;
+                if key_bits(key, height) >> child_height != path.as_u256() {
+                    // The path leaves the key, so the key is not in the trie.
+                    if i + 1 != proof.len() {
+                        return Err(invalid_proof("it continues past the key's absence"));
+                    }
+                    return Ok(StarkFelt::ZERO);
+                }
+                height = child_height;
+                expected_hash = *child;
+            }
+        }
+    }
+    if height != 0 {
+        // Only the empty trie, whose root is zero, has a proof that ends above the leaves.
+        if proof.is_empty() && *root == StarkFelt::ZERO {
+            return Ok(StarkFelt::ZERO);
+        }
+        return Err(invalid_proof("it ends above the leaves"));
+    }
+    Ok(expected_hash)
+}
+
+pub(crate) fn invalid_proof(reason: &str) -> StarknetApiError {
+    StarknetApiError::InvalidProof(reason.to_owned())
+}
+
+/// The path of an [`EdgeNode`]: the `length` least significant bits of `value`, where the most
+/// significant of them is the first step down the trie and a set bit means right.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub struct EdgePath {
+    pub value: StarkFelt,
+    #[serde(rename = "len")]
+    pub length: u8,
+}
+
+impl EdgePath {
+    fn new(value: U256, length: u8) -> Self {
+        let mut bytes = [0u8; 32];
+        value.to_big_endian(&mut bytes);
+        // A path is at most 251 bits long, so it is in the field.
+        Self { value: StarkFelt::new_unchecked(bytes), length }
+    }
+
+    fn as_u256(&self) -> U256 {
+        U256::from_big_endian(self.value.bytes())
+    }
+}
+
+fn insert_into(
+    node: Option<PatriciaNode>,
+    key: U256,
+    height: u8,
+    value: StarkFelt,
+) -> PatriciaNode {
+    let Some(node) = node else {
+        return new_edge(key_bits(key, height), height, PatriciaNode::Leaf(value));
+    };
+    match node {
+        PatriciaNode::Leaf(_) => PatriciaNode::Leaf(value),
+        PatriciaNode::Binary(BinaryNode { left, right, .. }) => {
+            let child_height = height - 1;
+            if key.bit(child_height.into()) {
+                new_binary(*left, insert_into(Some(*right), key, child_height, value))
+            } else {
+                new_binary(insert_into(Some(*left), key, child_height, value), *right)
+            }
+        }
+        PatriciaNode::Edge(EdgeNode { path, child, .. }) => {
+            let path_value = path.as_u256();
+            let child_height = height - path.length;
+            let key_path = key_bits(key, height) >> child_height;
+            let differing_bits = u8::try_from((path_value ^ key_path).bits())
+                .expect("A path is at most 251 bits long.");
+            if differing_bits == 0 {
+                let child = insert_into(Some(*child), key, child_height, value);
+                return new_edge(path_value, path.length, child);
+            }
+
+            // The key leaves the path at a new binary node, below the common part of the path.
+            let common_length = path.length - differing_bits;
+            let binary_height = height - common_length;
+            let existing_branch =
+                new_edge(key_bits(path_value, differing_bits - 1), differing_bits - 1, *child);
+            let new_branch = new_edge(
+                key_bits(key, binary_height - 1),
+                binary_height - 1,
+                PatriciaNode::Leaf(value),
+            );
+            let (left, right) = if key.bit((binary_height - 1).into()) {
+                (existing_branch, new_branch)
+            } else {
+                (new_branch, existing_branch)
+            };
+            new_edge(path_value >> differing_bits, common_length, new_binary(left, right))
+        }
+    }
+}
+
+fn delete_from(node: PatriciaNode, key: U256, height: u8) -> Option<PatriciaNode> {
+    match node {
+        PatriciaNode::Leaf(_) => None,
+        PatriciaNode::Binary(BinaryNode { left, right, .. }) => {
+            let child_height = height - 1;
+            let (left, right) = if key.bit(child_height.into()) {
+                (Some(*left), delete_from(*right, key, child_height))
+            } else {
+                (delete_from(*left, key, child_height), Some(*right))
+            };
+            match (left, right) {
+                (Some(left), Some(right)) => Some(new_binary(left, right)),
+                (Some(left), None) => Some(new_edge(U256::zero(), 1, left)),
+                (None, Some(right)) => Some(new_edge(U256::one(), 1, right)),
+                (None, None) => None,
+            }
+        }
+        PatriciaNode::Edge(edge) => {
+            let child_height = height - edge.path.length;
+            if key_bits(key, height) >> child_height != edge.path.as_u256() {
+                // The key is not in the trie, so the edge and its cached hash remain.
+                return Some(PatriciaNode::Edge(edge));
+            }
+            let EdgeNode { path, child, .. } = edge;
+            delete_from(*child, key, child_height)
+                .map(|child| new_edge(path.as_u256(), path.length, child))
+        }
+    }
+}
+
+fn new_binary(left: PatriciaNode, right: PatriciaNode) -> PatriciaNode {
+    PatriciaNode::Binary(BinaryNode {
+        left: Box::new(left),
+        right: Box::new(right),
+        hash: CachedHash::default(),
+    })
+}
+
+// Returns an edge node with the given path above the child, merging it with the child if it is an
+// edge node itself.
+fn new_edge(path: U256, length: u8, child: PatriciaNode) -> PatriciaNode {
+    if length == 0 {
+        return child;
+    }
+    match child {
+        PatriciaNode::Edge(EdgeNode { path: child_path, child, .. }) => {
+            let merged_path = (path << child_path.length) | child_path.as_u256();
+            PatriciaNode::Edge(EdgeNode {
+                path: EdgePath::new(merged_path, length + child_path.length),
+                child,
+                hash: CachedHash::default(),
+            })
+        }
+        _ => PatriciaNode::Edge(EdgeNode {
+            path: EdgePath::new(path, length),
+            child: Box::new(child),
+            hash: CachedHash::default(),
+        }),
+    }
+}
+
+// Returns the `length` least significant bits of the key.
+fn key_bits(key: U256, length: u8) -> U256 {
+    key & ((U256::one() << length) - 1)
+}
+
+fn key_as_u256(key: &StorageKey) -> U256 {
+    U256::from_big_endian(key.0.key().bytes())
+}
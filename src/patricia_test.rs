@@ -0,0 +1,255 @@
+use assert_matches::assert_matches;
+
+use crate::core::PatriciaKey;
+use crate::hash::{Pedersen, Poseidon, StarkFelt, StarkHash, pedersen_hash, poseidon_hash};
+use crate::patricia::{
+    EdgePath, PatriciaNode, PatriciaTrie, ProofNode, calculate_root, verify_proof,
+};
+use crate::state::StorageKey;
+use crate::{StarknetApiError, patricia_key, stark_felt};
+
+fn get_keys_and_values(n: u64) -> Vec<(StorageKey, StarkFelt)> {
+    (1..=n)
+        .map(|i| {
+            // Spread the keys over the whole trie.
+            let key = pedersen_hash(&stark_felt!(i), &StarkFelt::ZERO);
+            let mut bytes = [0u8; 32];
+            bytes.copy_from_slice(key.bytes());
+            bytes[0] &= 0x03;
+            let key = StarkFelt::new(bytes).unwrap();
+            (StorageKey(PatriciaKey::try_from(key).unwrap()), stark_felt!(i + 100))
+        })
+        .collect()
+}
+
+#[test]
+fn empty_tree_root() {
+    assert_eq!(calculate_root::<Pedersen>(&[]), StarkFelt::ZERO);
+}
+
+#[test]
+fn single_leaf_root() {
+    let leaf = stark_felt!("0x12");
+    // An edge of length 64 from the root to the leaf at index 0.
+    let expected = poseidon_hash(&leaf, &StarkFelt::ZERO) + stark_felt!(64_u64);
+    assert_eq!(calculate_root::<Poseidon>(&[leaf]), expected);
+}
+
+#[test]
+fn three_leaves_root() {
+    let leaves = [stark_felt!("0x12"), stark_felt!("0x34"), stark_felt!("0x56")];
+    // The leaves 0 and 1 share a binary node, and the leaf 2 hangs from an edge of length 1.
+    let left = pedersen_hash(&leaves[0], &leaves[1]);
+    let right = pedersen_hash(&leaves[2], &StarkFelt::ZERO) + stark_felt!(1_u64);
+    let binary = pedersen_hash(&left, &right);
+    // An edge of length 62 from the root to the binary node above the leaves.
+    let expected = pedersen_hash(&binary, &StarkFelt::ZERO) + stark_felt!(62_u64);
+    assert_eq!(calculate_root::<Pedersen>(&leaves), expected);
+}
+
+#[test]
+fn trie_single_key() {
+    let mut trie = PatriciaTrie::<Pedersen>::new();
+    let key = StorageKey(patricia_key!("0x5"));
+    trie.insert(&key, stark_felt!("0x7"));
+
+    let expected = pedersen_hash(&stark_felt!("0x7"), &stark_felt!("0x5")) + stark_felt!(251_u64);
+    assert_eq!(trie.root(), expected);
+    assert_eq!(trie.get(&key), stark_felt!("0x7"));
+    assert_eq!(trie.get(&StorageKey(patricia_key!("0x4"))), StarkFelt::ZERO);
+    assert_eq!(
+        trie.root_node().map(|node| match node {
+            PatriciaNode::Edge(edge) => edge.path,
+            _ => panic!("Expected an edge node at the root."),
+        }),
+        Some(EdgePath { value: stark_felt!("0x5"), length: 251 })
+    );
+}
+
+#[test]
+fn trie_two_keys() {
+    let mut trie = PatriciaTrie::<Pedersen>::new();
+    trie.insert(&StorageKey(patricia_key!("0x4")), stark_felt!("0x1"));
+    trie.insert(&StorageKey(patricia_key!("0x6")), stark_felt!("0x2"));
+
+    // The keys part at the second least significant bit.
+    let left = pedersen_hash(&stark_felt!("0x1"), &StarkFelt::ZERO) + stark_felt!(1_u64);
+    let right = pedersen_hash(&stark_felt!("0x2"), &StarkFelt::ZERO) + stark_felt!(1_u64);
+    let binary = pedersen_hash(&left, &right);
+    let expected = pedersen_hash(&binary, &stark_felt!("0x1")) + stark_felt!(249_u64);
+    assert_eq!(trie.root(), expected);
+}
+
+#[test]
+fn trie_root_is_independent_of_insertion_order() {
+    let keys_and_values = get_keys_and_values(20);
+
+    let mut trie = PatriciaTrie::<Poseidon>::new();
+    for (key, value) in &keys_and_values {
+        trie.insert(key, *value);
+    }
+    let mut reversed_trie = PatriciaTrie::<Poseidon>::new();
+    for (key, value) in keys_and_values.iter().rev() {
+        reversed_trie.insert(key, *value);
+    }
+
+    assert_eq!(trie, reversed_trie);
+    assert_eq!(trie.root(), reversed_trie.root());
+    for (key, value) in &keys_and_values {
+        assert_eq!(trie.get(key), *value);
+    }
+}
+
+#[test]
+fn trie_delete() {
+    let keys_and_values = get_keys_and_values(20);
+    let (kept, deleted) = keys_and_values.split_at(10);
+
+    let mut expected_trie = PatriciaTrie::<Pedersen>::new();
+    for (key, value) in kept {
+        expected_trie.insert(key, *value);
+    }
+
+    let mut trie = PatriciaTrie::<Pedersen>::new();
+    for (key, value) in &keys_and_values {
+        trie.insert(key, *value);
+    }
+    for (i, (key, _)) in deleted.iter().enumerate() {
+        // Setting a key to zero deletes it.
+        if i % 2 == 0 {
+            trie.delete(key);
+        } else {
+            trie.insert(key, StarkFelt::ZERO);
+        }
+        assert_eq!(trie.get(key), StarkFelt::ZERO);
+    }
+    assert_eq!(trie, expected_trie);
+    assert_eq!(trie.root(), expected_trie.root());
+
+    for (key, _) in kept {
+        trie.delete(key);
+    }
+    assert_eq!(trie.root_node(), None);
+    assert_eq!(trie.root(), StarkHash::default());
+}
+
+#[test]
+fn trie_update_value() {
+    let mut trie = PatriciaTrie::<Pedersen>::new();
+    let key = StorageKey(patricia_key!("0x5"));
+    trie.insert(&key, stark_felt!("0x7"));
+    trie.insert(&key, stark_felt!("0x8"));
+
+    let mut expected_trie = PatriciaTrie::<Pedersen>::new();
+    expected_trie.insert(&key, stark_felt!("0x8"));
+    assert_eq!(trie.root(), expected_trie.root());
+}
+
+#[test]
+fn trie_root_after_changes_to_a_hashed_trie() {
+    let keys_and_values = get_keys_and_values(20);
+    let mut trie = PatriciaTrie::<Pedersen>::new();
+    for (key, value) in &keys_and_values {
+        trie.insert(key, *value);
+    }
+    // Hash the nodes before changing the trie.
+    trie.root();
+
+    let mut expected_trie = PatriciaTrie::<Pedersen>::new();
+    for (i, (key, value)) in keys_and_values.iter().enumerate() {
+        match i % 3 {
+            0 => trie.delete(key),
+            1 => {
+                trie.insert(key, *value + StarkFelt::ONE);
+                expected_trie.insert(key, *value + StarkFelt::ONE);
+            }
+            _ => expected_trie.insert(key, *value),
+        }
+    }
+    // Cached hashes are ignored when comparing tries.
+    assert_eq!(trie, expected_trie);
+    assert_eq!(trie.root(), expected_trie.root());
+}
+
+#[test]
+fn proof_of_membership_and_absence() {
+    let keys_and_values = get_keys_and_values(20);
+    let mut trie = PatriciaTrie::<Pedersen>::new();
+    for (key, value) in &keys_and_values {
+        trie.insert(key, *value);
+    }
+    let root = trie.root();
+
+    for (key, value) in &keys_and_values {
+        let proof = trie.get_proof(key);
+        assert_eq!(verify_proof::<Pedersen>(&root, key, &proof).unwrap(), *value);
+    }
+
+    let absent_key = StorageKey(patricia_key!("0x1234"));
+    let proof = trie.get_proof(&absent_key);
+    assert_eq!(verify_proof::<Pedersen>(&root, &absent_key, &proof).unwrap(), StarkFelt::ZERO);
+}
+
+#[test]
+fn proof_in_empty_trie() {
+    let trie = PatriciaTrie::<Poseidon>::new();
+    let key = StorageKey(patricia_key!("0x1"));
+    let proof = trie.get_proof(&key);
+    assert!(proof.is_empty());
+    assert_eq!(verify_proof::<Poseidon>(&trie.root(), &key, &proof).unwrap(), StarkFelt::ZERO);
+}
+
+#[test]
+fn invalid_proofs() {
+    let keys_and_values = get_keys_and_values(5);
+    let mut trie = PatriciaTrie::<Pedersen>::new();
+    for (key, value) in &keys_and_values {
+        trie.insert(key, *value);
+    }
+    let root = trie.root();
+    let (key, _) = &keys_and_values[0];
+    let proof = trie.get_proof(key);
+
+    // A proof against another root.
+    assert_matches!(
+        verify_proof::<Pedersen>(&stark_felt!("0x1"), key, &proof),
+        Err(StarknetApiError::InvalidProof(_))
+    );
+    // A proof with a tampered node.
+    let mut tampered_proof = proof.clone();
+    match tampered_proof.last_mut().unwrap() {
+        ProofNode::Binary { left, .. } => *left = stark_felt!("0x1"),
+        ProofNode::Edge { child, .. } => *child = stark_felt!("0x1"),
+    }
+    assert_matches!(
+        verify_proof::<Pedersen>(&root, key, &tampered_proof),
+        Err(StarknetApiError::InvalidProof(_))
+    );
+    // A truncated proof.
+    assert_matches!(
+        verify_proof::<Pedersen>(&root, key, &proof[..proof.len() - 1]),
+        Err(StarknetApiError::InvalidProof(_))
+    );
+    // An empty proof of a non-empty trie.
+    assert_matches!(
+        verify_proof::<Pedersen>(&root, key, &[]),
+        Err(StarknetApiError::InvalidProof(_))
+    );
+}
+
+#[test]
+fn proof_node_serde() {
+    let nodes = vec![
+        ProofNode::Binary { left: stark_felt!("0x1"), right: stark_felt!("0x2") },
+        ProofNode::Edge {
+            child: stark_felt!("0x3"),
+            path: EdgePath { value: stark_felt!("0x4"), length: 5 },
+        },
+    ];
+    let json = serde_json::json!([
+        {"binary": {"left": "0x1", "right": "0x2"}},
+        {"edge": {"child": "0x3", "path": {"value": "0x4", "len": 5}}},
+    ]);
+    assert_eq!(serde_json::to_value(&nodes).unwrap(), json);
+    assert_eq!(serde_json::from_value::<Vec<ProofNode>>(json).unwrap(), nodes);
+}
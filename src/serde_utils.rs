@@ -3,9 +3,9 @@
 #[path = "serde_utils_test.rs"]
 mod serde_utils_test;
 
+use serde::Deserializer;
 use serde::de::{Deserialize, Visitor};
 use serde::ser::{Serialize, SerializeTuple};
-use serde::Deserializer;
 
 use crate::deprecated_contract_class::ContractClassAbiEntry;
 
@@ -144,3 +144,69 @@ where
         Err(_) => Ok(None),
     }
 }
+
+/// Serializes a JSON value as Python's `json.dumps(value, sort_keys=True)` does: with ", " and ": "
+/// separators, the keys of objects sorted and all the characters outside of printable ASCII
+/// escaped.
+pub fn python_json_dumps(value: &serde_json::Value) -> String {
+    let mut output = String::new();
+    write_python_json(value, &mut output);
+    output
+}
+
+fn write_python_json(value: &serde_json::Value, output: &mut String) {
+    match value {
+        serde_json::Value::Null => output.push_str("null"),
+        serde_json::Value::Bool(value) => output.push_str(if *value { "true" } else { "false" }),
+        serde_json::Value::Number(number) => output.push_str(&number.to_string()),
+        serde_json::Value::String(string) => write_python_json_string(string, output),
+        serde_json::Value::Array(values) => {
+            output.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    output.push_str(", ");
+                }
+                write_python_json(value, output);
+            }
+            output.push(']');
+        }
+        serde_json::Value::Object(object) => {
+            let mut entries: Vec<_> = object.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            output.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    output.push_str(", ");
+                }
+                write_python_json_string(key, output);
+                output.push_str(": ");
+                write_python_json(value, output);
+            }
+            output.push('}');
+        }
+    }
+}
+
+fn write_python_json_string(string: &str, output: &mut String) {
+    output.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            '\u{8}' => output.push_str("\\b"),
+            '\u{c}' => output.push_str("\\f"),
+            ' '..='~' => output.push(c),
+            // Python escapes characters outside of the basic multilingual plane as UTF-16
+            // surrogate pairs.
+            _ => {
+                for code_unit in c.encode_utf16(&mut [0; 2]) {
+                    output.push_str(&format!("\\u{code_unit:04x}"));
+                }
+            }
+        }
+    }
+    output.push('"');
+}
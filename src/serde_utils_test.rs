@@ -3,8 +3,8 @@ use serde::Deserialize;
 
 use crate::deprecated_contract_class::{ContractClassAbiEntry, FunctionAbiEntry, TypedParameter};
 use crate::serde_utils::{
-    bytes_from_hex_str, deserialize_optional_contract_class_abi_entry_vector, hex_str_from_bytes,
-    BytesAsHex, InnerDeserializationError,
+    BytesAsHex, InnerDeserializationError, bytes_from_hex_str,
+    deserialize_optional_contract_class_abi_entry_vector, hex_str_from_bytes, python_json_dumps,
 };
 
 #[test]
@@ -175,3 +175,17 @@ fn deserialize_optional_contract_class_abi_entry_vector_none() {
     let res: DummyContractClass = serde_json::from_str(json).unwrap();
     assert_eq!(res, DummyContractClass { abi: None });
 }
+
+#[test]
+fn python_json_dumps_formatting() {
+    // The expected strings are the output of Python's json.dumps(value, sort_keys=True).
+    let value = serde_json::json!({
+        "b": [1, true, null, {}, []],
+        "a": {"y": "x", "x": -2},
+        "c": "quote\" backslash\\ newline\n tab\t del\u{7f} é 𝄞",
+    });
+    assert_eq!(
+        python_json_dumps(&value),
+        r#"{"a": {"x": -2, "y": "x"}, "b": [1, true, null, {}, []], "c": "quote\" backslash\\ newline\n tab\t del\u007f \u00e9 \ud834\udd1e"}"#
+    );
+}
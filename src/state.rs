@@ -2,20 +2,35 @@
 #[path = "state_test.rs"]
 mod state_test;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::Debug;
 
 use indexmap::IndexMap;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 
 use crate::block::{BlockHash, BlockNumber};
+use crate::block_commitment::StateDiffCommitment;
 use crate::core::{
     ClassHash, CompiledClassHash, ContractAddress, EntryPointSelector, GlobalRoot, Nonce,
     PatriciaKey,
 };
+use crate::data_availability::{
+    L1DataAvailabilityMode, blobs_to_felts, calldata_to_felts, felts_to_blobs, felts_to_calldata,
+    invalid_encoding,
+};
 use crate::deprecated_contract_class::ContractClass as DeprecatedContractClass;
-use crate::hash::{StarkFelt, StarkHash};
-use crate::{impl_from_through_intermediate, StarknetApiError};
+use crate::hash::{
+    HashChain, Poseidon, StarkFelt, StarkHash, StarkHasher, ascii_as_felt, lazy_ascii_as_felt,
+    starknet_keccak,
+};
+use crate::state_reader::StateReader;
+use crate::{StarknetApiError, impl_from_through_intermediate};
+
+static STARKNET_STATE_DIFF0: Lazy<StarkFelt> = lazy_ascii_as_felt!("STARKNET_STATE_DIFF0");
+
+/// The version of the class hash scheme of all the Cairo 1 classes so far.
+pub const CONTRACT_CLASS_VERSION: &str = "0.1.0";
 
 pub type DeclaredClasses = IndexMap<ClassHash, ContractClass>;
 pub type DeprecatedDeclaredClasses = IndexMap<ClassHash, DeprecatedContractClass>;
@@ -31,9 +46,13 @@ pub struct StateUpdate {
 }
 
 /// The differences between two states.
-// Invariant: Addresses are strictly increasing.
+///
+/// Create it with [`StateDiff::new`], which enforces its invariants; the fields are public for
+/// backward compatibility, so a diff that is built or deserialized otherwise should be
+/// [validated](`StateDiff::validate`).
+// Invariant: Addresses, class hashes and storage keys are strictly increasing.
 // Invariant: Class hashes of declared_classes and deprecated_declared_classes are exclusive.
-// TODO(yair): Enforce this invariant.
+// Invariant: All the values are in the field.
 #[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
 pub struct StateDiff {
     pub deployed_contracts: IndexMap<ContractAddress, ClassHash>,
@@ -44,10 +63,13 @@ pub struct StateDiff {
     pub replaced_classes: IndexMap<ContractAddress, ClassHash>,
 }
 
-// Invariant: Addresses are strictly increasing.
-// The invariant is enforced as [`ThinStateDiff`] is created only from [`starknet_api`][`StateDiff`]
-// where the addresses are strictly increasing.
-#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+/// A [`StateDiff`] without the declared classes themselves.
+///
+/// Create it from a [`StateDiff`] or with [`ThinStateDiff::new`], which enforce its invariants.
+// Invariant: Addresses, class hashes and storage keys are strictly increasing.
+// Invariant: Class hashes of declared_classes and deprecated_declared_classes are exclusive.
+// Invariant: All the values are in the field.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
 pub struct ThinStateDiff {
     pub deployed_contracts: IndexMap<ContractAddress, ClassHash>,
     pub storage_diffs: IndexMap<ContractAddress, IndexMap<StorageKey, StarkFelt>>,
@@ -57,7 +79,230 @@ pub struct ThinStateDiff {
     pub replaced_classes: IndexMap<ContractAddress, ClassHash>,
 }
 
+impl StateDiff {
+    /// Returns a new diff, sorted as by [`StateDiff::normalize`], after checking that no class is
+    /// declared twice and that all the values are in the field.
+    pub fn new(
+        deployed_contracts: IndexMap<ContractAddress, ClassHash>,
+        storage_diffs: IndexMap<ContractAddress, IndexMap<StorageKey, StarkFelt>>,
+        declared_classes: IndexMap<ClassHash, (CompiledClassHash, ContractClass)>,
+        deprecated_declared_classes: IndexMap<ClassHash, DeprecatedContractClass>,
+        nonces: IndexMap<ContractAddress, Nonce>,
+        replaced_classes: IndexMap<ContractAddress, ClassHash>,
+    ) -> Result<Self, StarknetApiError> {
+        let mut diff = Self {
+            deployed_contracts,
+            storage_diffs,
+            declared_classes,
+            deprecated_declared_classes,
+            nonces,
+            replaced_classes,
+        };
+        diff.normalize();
+        diff.validate()?;
+        Ok(diff)
+    }
+
+    /// Sorts all the maps of the diff, including the storage diff of each contract, by key.
+    pub fn normalize(&mut self) {
+        self.deployed_contracts.sort_keys();
+        sort_storage_diffs(&mut self.storage_diffs);
+        self.declared_classes.sort_keys();
+        self.deprecated_declared_classes.sort_keys();
+        self.nonces.sort_keys();
+        self.replaced_classes.sort_keys();
+    }
+
+    /// Returns the net effect of this diff followed by a later one, as by
+    /// [`ThinStateDiff::merge`].
+    pub fn merge(self, later: StateDiff) -> StateDiff {
+        let (thin_diff, mut declared_classes, mut deprecated_declared_classes) =
+            ThinStateDiff::from_state_diff(self);
+        let (later_thin_diff, later_declared_classes, later_deprecated_declared_classes) =
+            ThinStateDiff::from_state_diff(later);
+        declared_classes.extend(later_declared_classes);
+        deprecated_declared_classes.extend(later_deprecated_declared_classes);
+
+        let merged = thin_diff.merge(later_thin_diff);
+        StateDiff {
+            deployed_contracts: merged.deployed_contracts,
+            storage_diffs: merged.storage_diffs,
+            declared_classes: merged
+                .declared_classes
+                .into_iter()
+                .map(|(class_hash, compiled_class_hash)| {
+                    let class = declared_classes
+                        .swap_remove(&class_hash)
+                        .expect("A merged declared class is declared in one of the diffs.");
+                    (class_hash, (compiled_class_hash, class))
+                })
+                .collect(),
+            deprecated_declared_classes: merged
+                .deprecated_declared_classes
+                .into_iter()
+                .map(|class_hash| {
+                    let class = deprecated_declared_classes
+                        .swap_remove(&class_hash)
+                        .expect("A merged deprecated class is declared in one of the diffs.");
+                    (class_hash, class)
+                })
+                .collect(),
+            nonces: merged.nonces,
+            replaced_classes: merged.replaced_classes,
+        }
+    }
+
+    /// Checks the invariants of the diff.
+    pub fn validate(&self) -> Result<(), StarknetApiError> {
+        validate_sorted("deployed_contracts", self.deployed_contracts.keys())?;
+        validate_sorted("storage_diffs", self.storage_diffs.keys())?;
+        for diff in self.storage_diffs.values() {
+            validate_sorted("storage_diffs", diff.keys())?;
+        }
+        validate_sorted("declared_classes", self.declared_classes.keys())?;
+        validate_sorted("deprecated_declared_classes", self.deprecated_declared_classes.keys())?;
+        validate_sorted("nonces", self.nonces.keys())?;
+        validate_sorted("replaced_classes", self.replaced_classes.keys())?;
+
+        validate_declared_classes(
+            self.declared_classes.keys(),
+            self.deprecated_declared_classes.keys(),
+        )?;
+
+        validate_in_field(
+            "deployed_contracts",
+            self.deployed_contracts.values().map(|hash| &hash.0),
+        )?;
+        validate_in_field("storage_diffs", self.storage_diffs.values().flat_map(IndexMap::values))?;
+        validate_in_field(
+            "declared_classes",
+            self.declared_classes.iter().flat_map(|(class_hash, (compiled_class_hash, _))| {
+                [&class_hash.0, &compiled_class_hash.0]
+            }),
+        )?;
+        validate_in_field(
+            "deprecated_declared_classes",
+            self.deprecated_declared_classes.keys().map(|hash| &hash.0),
+        )?;
+        validate_in_field("nonces", self.nonces.values().map(|nonce| &nonce.0))?;
+        validate_in_field("replaced_classes", self.replaced_classes.values().map(|hash| &hash.0))?;
+        Ok(())
+    }
+}
+
 impl ThinStateDiff {
+    /// Returns a new diff, sorted as by [`ThinStateDiff::normalize`], after checking that no class
+    /// is declared twice and that all the values are in the field.
+    pub fn new(
+        deployed_contracts: IndexMap<ContractAddress, ClassHash>,
+        storage_diffs: IndexMap<ContractAddress, IndexMap<StorageKey, StarkFelt>>,
+        declared_classes: IndexMap<ClassHash, CompiledClassHash>,
+        deprecated_declared_classes: Vec<ClassHash>,
+        nonces: IndexMap<ContractAddress, Nonce>,
+        replaced_classes: IndexMap<ContractAddress, ClassHash>,
+    ) -> Result<Self, StarknetApiError> {
+        let mut diff = Self {
+            deployed_contracts,
+            storage_diffs,
+            declared_classes,
+            deprecated_declared_classes,
+            nonces,
+            replaced_classes,
+        };
+        diff.normalize();
+        diff.validate()?;
+        Ok(diff)
+    }
+
+    /// Sorts all the maps of the diff, including the storage diff of each contract, by key, and
+    /// the deprecated declared classes.
+    pub fn normalize(&mut self) {
+        self.deployed_contracts.sort_keys();
+        sort_storage_diffs(&mut self.storage_diffs);
+        self.declared_classes.sort_keys();
+        self.deprecated_declared_classes.sort();
+        self.nonces.sort_keys();
+        self.replaced_classes.sort_keys();
+    }
+
+    /// Returns the net effect of this diff followed by a later one, normalized.
+    ///
+    /// The later diff wins on storage values, nonces, classes of contracts and compiled class
+    /// hashes. A contract that is deployed and then has its class replaced is deployed with the
+    /// new class.
+    pub fn merge(mut self, later: ThinStateDiff) -> ThinStateDiff {
+        for (address, class_hash) in later.deployed_contracts {
+            self.replaced_classes.swap_remove(&address);
+            self.deployed_contracts.insert(address, class_hash);
+        }
+        for (address, class_hash) in later.replaced_classes {
+            match self.deployed_contracts.get_mut(&address) {
+                Some(deployed_class_hash) => *deployed_class_hash = class_hash,
+                None => {
+                    self.replaced_classes.insert(address, class_hash);
+                }
+            }
+        }
+        for (address, diff) in later.storage_diffs {
+            self.storage_diffs.entry(address).or_default().extend(diff);
+        }
+        self.nonces.extend(later.nonces);
+        self.declared_classes.extend(later.declared_classes);
+        let deprecated_declared_classes: HashSet<ClassHash> =
+            self.deprecated_declared_classes.iter().copied().collect();
+        self.deprecated_declared_classes.extend(
+            later
+                .deprecated_declared_classes
+                .into_iter()
+                .filter(|class_hash| !deprecated_declared_classes.contains(class_hash)),
+        );
+        self.normalize();
+        self
+    }
+
+    /// Returns the net effect of consecutive diffs, as by [`ThinStateDiff::merge`].
+    pub fn squash(diffs: impl IntoIterator<Item = ThinStateDiff>) -> ThinStateDiff {
+        diffs.into_iter().fold(ThinStateDiff::default(), ThinStateDiff::merge)
+    }
+
+    /// Checks the invariants of the diff.
+    pub fn validate(&self) -> Result<(), StarknetApiError> {
+        validate_sorted("deployed_contracts", self.deployed_contracts.keys())?;
+        validate_sorted("storage_diffs", self.storage_diffs.keys())?;
+        for diff in self.storage_diffs.values() {
+            validate_sorted("storage_diffs", diff.keys())?;
+        }
+        validate_sorted("declared_classes", self.declared_classes.keys())?;
+        // Checks the deprecated declared classes are unique before checking they are sorted, to
+        // report duplicates as such.
+        validate_declared_classes(
+            self.declared_classes.keys(),
+            self.deprecated_declared_classes.iter(),
+        )?;
+        validate_sorted("deprecated_declared_classes", self.deprecated_declared_classes.iter())?;
+        validate_sorted("nonces", self.nonces.keys())?;
+        validate_sorted("replaced_classes", self.replaced_classes.keys())?;
+
+        validate_in_field(
+            "deployed_contracts",
+            self.deployed_contracts.values().map(|hash| &hash.0),
+        )?;
+        validate_in_field("storage_diffs", self.storage_diffs.values().flat_map(IndexMap::values))?;
+        validate_in_field(
+            "declared_classes",
+            self.declared_classes.iter().flat_map(|(class_hash, compiled_class_hash)| {
+                [&class_hash.0, &compiled_class_hash.0]
+            }),
+        )?;
+        validate_in_field(
+            "deprecated_declared_classes",
+            self.deprecated_declared_classes.iter().map(|hash| &hash.0),
+        )?;
+        validate_in_field("nonces", self.nonces.values().map(|nonce| &nonce.0))?;
+        validate_in_field("replaced_classes", self.replaced_classes.values().map(|hash| &hash.0))?;
+        Ok(())
+    }
+
     // Returns also the declared classes without cloning them.
     pub fn from_state_diff(diff: StateDiff) -> (Self, DeclaredClasses, DeprecatedDeclaredClasses) {
         (
@@ -84,6 +329,313 @@ impl ThinStateDiff {
             diff.deprecated_declared_classes,
         )
     }
+
+    /// Returns the number of changes in the diff: deployed and replaced contracts, declared
+    /// classes, storage entries and nonces.
+    pub fn len(&self) -> usize {
+        self.deployed_contracts.len()
+            + self.declared_classes.len()
+            + self.deprecated_declared_classes.len()
+            + self.nonces.len()
+            + self.replaced_classes.len()
+            + self.storage_diffs.values().map(IndexMap::len).sum::<usize>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the commitment to the diff that blocks hash from Starknet 0.13.2, which is the
+    /// Poseidon hash of:
+    /// ["STARKNET_STATE_DIFF0",
+    ///  num_updated_contracts, (address, class_hash) of deployed and replaced contracts...,
+    ///  num_declared_classes, (class_hash, compiled_class_hash)...,
+    ///  num_deprecated_declared_classes, class_hash...,
+    ///  1, 0,
+    ///  num_contracts_with_storage_diffs, (address, num_diffs, (key, value)...)...,
+    ///  num_nonces, (address, nonce)...],
+    /// where every list is sorted by its first element and contracts with empty storage diffs are
+    /// skipped.
+    pub fn commitment(&self) -> StateDiffCommitment {
+        let updated_contracts: BTreeMap<_, _> =
+            self.deployed_contracts.iter().chain(self.replaced_classes.iter()).collect();
+        let mut hash_chain =
+            HashChain::new().chain(&STARKNET_STATE_DIFF0).chain(&updated_contracts.len().into());
+        for (address, class_hash) in updated_contracts {
+            hash_chain = hash_chain.chain(address.0.key()).chain(&class_hash.0);
+        }
+
+        let declared_classes: BTreeMap<_, _> = self.declared_classes.iter().collect();
+        hash_chain = hash_chain.chain(&declared_classes.len().into());
+        for (class_hash, compiled_class_hash) in declared_classes {
+            hash_chain = hash_chain.chain(&class_hash.0).chain(&compiled_class_hash.0);
+        }
+
+        let mut deprecated_declared_classes: Vec<StarkHash> =
+            self.deprecated_declared_classes.iter().map(|class_hash| class_hash.0).collect();
+        deprecated_declared_classes.sort();
+        hash_chain = hash_chain
+            .chain_size_and_elements(&deprecated_declared_classes)
+            // The number of data availability modes and the mode of the following diffs; L1 is the
+            // only one so far.
+            .chain(&StarkFelt::ONE)
+            .chain(&StarkFelt::ZERO);
+
+        let storage_diffs: BTreeMap<_, _> =
+            self.storage_diffs.iter().filter(|(_, diff)| !diff.is_empty()).collect();
+        hash_chain = hash_chain.chain(&storage_diffs.len().into());
+        for (address, diff) in storage_diffs {
+            hash_chain = hash_chain.chain(address.0.key()).chain(&diff.len().into());
+            let diff: BTreeMap<_, _> = diff.iter().collect();
+            for (key, value) in diff {
+                hash_chain = hash_chain.chain(key.0.key()).chain(value);
+            }
+        }
+
+        let nonces: BTreeMap<_, _> = self.nonces.iter().collect();
+        hash_chain = hash_chain.chain(&nonces.len().into());
+        for (address, nonce) in nonces {
+            hash_chain = hash_chain.chain(address.0.key()).chain(&nonce.0);
+        }
+
+        StateDiffCommitment(hash_chain.get_poseidon_hash())
+    }
+
+    /// Returns the diff as it is published on L1 in the given mode, given the state before the
+    /// diff: the felts
+    /// [num_contracts,
+    ///  (address, class_flag * 2^128 + nonce * 2^64 + num_storage_updates, \[class_hash\],
+    ///   (key, value)...)...,
+    ///  num_declared_classes, (class_hash, compiled_class_hash)...],
+    /// where a contract is listed if its storage, nonce or class changes, its class hash is
+    /// present only if its class flag is set, its nonce is its nonce after the diff, read from
+    /// the state if the diff does not change it, and every list is sorted by its first element.
+    /// Deprecated declared classes are not published.
+    ///
+    /// As calldata the felts are 32-byte words, and as blobs they are packed by
+    /// [`felts_to_blobs`].
+    pub fn encode_da(
+        &self,
+        state: &impl StateReader,
+        mode: L1DataAvailabilityMode,
+    ) -> Result<Vec<u8>, StarknetApiError> {
+        let felts = self.da_felts(state)?;
+        Ok(match mode {
+            L1DataAvailabilityMode::Calldata => felts_to_calldata(&felts),
+            L1DataAvailabilityMode::Blob => felts_to_blobs(&felts),
+        })
+    }
+
+    /// Returns the normalized diff published on L1 in the given mode, given the state before the
+    /// diff, see [`ThinStateDiff::encode_da`]. Only the nonces that differ from those in the state
+    /// are returned.
+    ///
+    /// L1 data does not tell a deployment from a class replacement, hence the contracts whose
+    /// class is updated are returned as deployed.
+    pub fn decode_da(
+        data: &[u8],
+        state: &impl StateReader,
+        mode: L1DataAvailabilityMode,
+    ) -> Result<ThinStateDiff, StarknetApiError> {
+        let felts = match mode {
+            L1DataAvailabilityMode::Calldata => calldata_to_felts(data)?,
+            L1DataAvailabilityMode::Blob => blobs_to_felts(data)?,
+        };
+        let mut felts = felts.into_iter();
+        let mut diff = ThinStateDiff::default();
+
+        let num_contracts = usize::try_from(next_da_felt(&mut felts, "number of contracts")?)?;
+        for _ in 0..num_contracts {
+            let address = ContractAddress::try_from(next_da_felt(&mut felts, "contract address")?)?;
+            let (class_updated, nonce, num_storage_updates) =
+                unpack_da_contract_word(next_da_felt(&mut felts, "contract updates")?)?;
+            if class_updated {
+                let class_hash = ClassHash(next_da_felt(&mut felts, "class hash")?);
+                diff.deployed_contracts.insert(address, class_hash);
+            }
+            let nonce = Nonce(nonce.into());
+            if nonce != state.get_nonce_at(address)? {
+                diff.nonces.insert(address, nonce);
+            }
+            let mut storage_diff = IndexMap::new();
+            for _ in 0..num_storage_updates {
+                let key = StorageKey::try_from(next_da_felt(&mut felts, "storage key")?)?;
+                storage_diff.insert(key, next_da_felt(&mut felts, "storage value")?);
+            }
+            if !storage_diff.is_empty() {
+                diff.storage_diffs.insert(address, storage_diff);
+            }
+        }
+
+        let num_declared_classes =
+            usize::try_from(next_da_felt(&mut felts, "number of declared classes")?)?;
+        for _ in 0..num_declared_classes {
+            let class_hash = ClassHash(next_da_felt(&mut felts, "declared class hash")?);
+            let compiled_class_hash =
+                CompiledClassHash(next_da_felt(&mut felts, "compiled class hash")?);
+            diff.declared_classes.insert(class_hash, compiled_class_hash);
+        }
+
+        // Blobs are padded with zeros.
+        if felts.any(|felt| felt != StarkFelt::ZERO) {
+            return Err(invalid_encoding("nonzero data follows the state diff".to_owned()));
+        }
+        diff.normalize();
+        Ok(diff)
+    }
+
+    fn da_felts(&self, state: &impl StateReader) -> Result<Vec<StarkFelt>, StarknetApiError> {
+        let updated_classes: BTreeMap<_, _> =
+            self.deployed_contracts.iter().chain(self.replaced_classes.iter()).collect();
+        let addresses: BTreeSet<&ContractAddress> = updated_classes
+            .keys()
+            .copied()
+            .chain(self.nonces.keys())
+            .chain(self.storage_diffs.iter().filter(|(_, diff)| !diff.is_empty()).map(|(a, _)| a))
+            .collect();
+
+        let mut felts = vec![StarkFelt::from(addresses.len())];
+        for address in addresses {
+            let class_hash = updated_classes.get(address);
+            let nonce = match self.nonces.get(address) {
+                Some(nonce) => *nonce,
+                None => state.get_nonce_at(*address)?,
+            };
+            let nonce = u64::try_from(nonce.0)?;
+            let storage_diff: BTreeMap<_, _> =
+                self.storage_diffs.get(address).into_iter().flatten().collect();
+            let num_storage_updates =
+                u64::try_from(storage_diff.len()).expect("Got 2^64 storage updates or more.");
+            felts.push(*address.0.key());
+            felts.push(pack_da_contract_word(class_hash.is_some(), nonce, num_storage_updates));
+            if let Some(class_hash) = class_hash {
+                felts.push(class_hash.0);
+            }
+            for (key, value) in storage_diff {
+                felts.push(*key.0.key());
+                felts.push(*value);
+            }
+        }
+
+        let declared_classes: BTreeMap<_, _> = self.declared_classes.iter().collect();
+        felts.push(StarkFelt::from(declared_classes.len()));
+        for (class_hash, compiled_class_hash) in declared_classes {
+            felts.push(class_hash.0);
+            felts.push(compiled_class_hash.0);
+        }
+        Ok(felts)
+    }
+}
+
+/// An error in the content of a [`StateDiff`] or a [`ThinStateDiff`].
+#[derive(thiserror::Error, Clone, Debug, Eq, PartialEq)]
+pub enum StateDiffError {
+    /// A class that is declared more than once.
+    #[error("Class {0} is declared more than once.")]
+    DuplicateDeclaredClass(ClassHash),
+    /// A class that is declared both as a Cairo 1 class and as a deprecated class.
+    #[error("Class {0} is declared both as a Cairo 1 class and as a deprecated class.")]
+    OverlappingDeclaredClass(ClassHash),
+    /// A value that is not in the field.
+    #[error("{field} holds {value}, which is not in the field.")]
+    OutOfRangeValue { field: &'static str, value: StarkFelt },
+    /// Keys that are not strictly increasing.
+    #[error("The keys of {0} are not strictly increasing.")]
+    UnsortedKeys(&'static str),
+    /// Previous values of a reversible diff that are not exactly those of the entries it changes.
+    #[error("The previous values of {0} do not match the entries that the diff changes.")]
+    PreviousValuesMismatch(&'static str),
+}
+
+// Returns the (address, key) pairs of storage diffs, in their order.
+fn storage_entries(
+    storage_diffs: &IndexMap<ContractAddress, IndexMap<StorageKey, StarkFelt>>,
+) -> Vec<(&ContractAddress, &StorageKey)> {
+    storage_diffs
+        .iter()
+        .flat_map(|(address, diff)| diff.keys().map(move |key| (address, key)))
+        .collect()
+}
+
+fn sort_storage_diffs(
+    storage_diffs: &mut IndexMap<ContractAddress, IndexMap<StorageKey, StarkFelt>>,
+) {
+    storage_diffs.sort_keys();
+    for diff in storage_diffs.values_mut() {
+        diff.sort_keys();
+    }
+}
+
+fn validate_sorted<'a, K: Ord + 'a>(
+    field: &'static str,
+    mut keys: impl Iterator<Item = &'a K>,
+) -> Result<(), StateDiffError> {
+    let Some(mut previous) = keys.next() else {
+        return Ok(());
+    };
+    for key in keys {
+        if key <= previous {
+            return Err(StateDiffError::UnsortedKeys(field));
+        }
+        previous = key;
+    }
+    Ok(())
+}
+
+fn validate_declared_classes<'a>(
+    declared_classes: impl Iterator<Item = &'a ClassHash>,
+    deprecated_declared_classes: impl Iterator<Item = &'a ClassHash>,
+) -> Result<(), StateDiffError> {
+    let declared_classes: HashSet<&ClassHash> = declared_classes.collect();
+    let mut seen_deprecated_classes = HashSet::new();
+    for class_hash in deprecated_declared_classes {
+        if !seen_deprecated_classes.insert(class_hash) {
+            return Err(StateDiffError::DuplicateDeclaredClass(*class_hash));
+        }
+        if declared_classes.contains(class_hash) {
+            return Err(StateDiffError::OverlappingDeclaredClass(*class_hash));
+        }
+    }
+    Ok(())
+}
+
+fn validate_in_field<'a>(
+    field: &'static str,
+    mut values: impl Iterator<Item = &'a StarkFelt>,
+) -> Result<(), StateDiffError> {
+    match values.find(|value| !value.is_in_field()) {
+        Some(value) => Err(StateDiffError::OutOfRangeValue { field, value: *value }),
+        None => Ok(()),
+    }
+}
+
+// class_flag * 2^128 + nonce * 2^64 + num_storage_updates, as bytes: the flag is the lowest bit of
+// byte 15, and the nonce and the number of updates are big-endian in bytes 16..24 and 24..32.
+fn pack_da_contract_word(class_updated: bool, nonce: u64, num_storage_updates: u64) -> StarkFelt {
+    let mut bytes = [0u8; 32];
+    bytes[15] = u8::from(class_updated);
+    bytes[16..24].copy_from_slice(&nonce.to_be_bytes());
+    bytes[24..].copy_from_slice(&num_storage_updates.to_be_bytes());
+    StarkFelt::new_unchecked(bytes)
+}
+
+fn unpack_da_contract_word(word: StarkFelt) -> Result<(bool, u64, u64), StarknetApiError> {
+    let bytes = word.bytes();
+    if bytes[..15].iter().any(|byte| *byte != 0) || bytes[15] > 1 {
+        return Err(invalid_encoding(format!("{word} is not a valid contract updates word")));
+    }
+    let mut nonce = [0u8; 8];
+    nonce.copy_from_slice(&bytes[16..24]);
+    let mut num_storage_updates = [0u8; 8];
+    num_storage_updates.copy_from_slice(&bytes[24..]);
+    Ok((bytes[15] == 1, u64::from_be_bytes(nonce), u64::from_be_bytes(num_storage_updates)))
+}
+
+fn next_da_felt(
+    felts: &mut impl Iterator<Item = StarkFelt>,
+    name: &str,
+) -> Result<StarkFelt, StarknetApiError> {
+    felts.next().ok_or_else(|| invalid_encoding(format!("the data ends before the {name}")))
 }
 
 impl From<StateDiff> for ThinStateDiff {
@@ -92,6 +644,106 @@ impl From<StateDiff> for ThinStateDiff {
     }
 }
 
+/// A [`ThinStateDiff`] together with the values that it overwrites, so that it can be undone, e.g.
+/// in a reorg.
+// Invariant: The previous values are exactly of the entries that the diff changes.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct ReversibleStateDiff {
+    pub diff: ThinStateDiff,
+    /// The storage values before the diff, of the keys that it writes.
+    pub previous_storage_values: IndexMap<ContractAddress, IndexMap<StorageKey, StarkFelt>>,
+    /// The nonces before the diff, of the contracts whose nonces it sets.
+    pub previous_nonces: IndexMap<ContractAddress, Nonce>,
+    /// The class hashes before the diff, of the contracts that it deploys (zero) or whose classes
+    /// it replaces.
+    pub previous_class_hashes: IndexMap<ContractAddress, ClassHash>,
+}
+
+impl ReversibleStateDiff {
+    /// Returns a new reversible diff, normalized, after checking that there is a previous value for
+    /// every entry the diff changes and for no other entry.
+    pub fn new(
+        mut diff: ThinStateDiff,
+        mut previous_storage_values: IndexMap<ContractAddress, IndexMap<StorageKey, StarkFelt>>,
+        mut previous_nonces: IndexMap<ContractAddress, Nonce>,
+        mut previous_class_hashes: IndexMap<ContractAddress, ClassHash>,
+    ) -> Result<Self, StarknetApiError> {
+        diff.normalize();
+        diff.validate()?;
+        sort_storage_diffs(&mut previous_storage_values);
+        previous_nonces.sort_keys();
+        previous_class_hashes.sort_keys();
+
+        if storage_entries(&diff.storage_diffs) != storage_entries(&previous_storage_values) {
+            return Err(StateDiffError::PreviousValuesMismatch("storage_diffs").into());
+        }
+        if !diff.nonces.keys().eq(previous_nonces.keys()) {
+            return Err(StateDiffError::PreviousValuesMismatch("nonces").into());
+        }
+        let changed_classes: BTreeMap<_, _> =
+            diff.deployed_contracts.iter().chain(diff.replaced_classes.iter()).collect();
+        if !changed_classes.keys().copied().eq(previous_class_hashes.keys()) {
+            return Err(StateDiffError::PreviousValuesMismatch("class_hashes").into());
+        }
+
+        Ok(Self { diff, previous_storage_values, previous_nonces, previous_class_hashes })
+    }
+
+    /// Returns a new reversible diff, with the previous values read from the state before the diff.
+    pub fn from_state_reader(
+        diff: ThinStateDiff,
+        state: &impl StateReader,
+    ) -> Result<Self, StarknetApiError> {
+        let mut previous_storage_values = IndexMap::new();
+        for (address, storage_diff) in &diff.storage_diffs {
+            let mut previous_values = IndexMap::new();
+            for key in storage_diff.keys() {
+                previous_values.insert(*key, state.get_storage_at(*address, *key)?);
+            }
+            previous_storage_values.insert(*address, previous_values);
+        }
+        let mut previous_nonces = IndexMap::new();
+        for address in diff.nonces.keys() {
+            previous_nonces.insert(*address, state.get_nonce_at(*address)?);
+        }
+        let mut previous_class_hashes = IndexMap::new();
+        for address in diff.deployed_contracts.keys().chain(diff.replaced_classes.keys()) {
+            previous_class_hashes.insert(*address, state.get_class_hash_at(*address)?);
+        }
+        Self::new(diff, previous_storage_values, previous_nonces, previous_class_hashes)
+    }
+
+    /// Returns the diff that undoes this one: it restores the previous storage values, nonces and
+    /// class hashes, where a zero class hash undoes a deployment, and its own previous values are
+    /// the values of this diff.
+    ///
+    /// A [`ThinStateDiff`] cannot undeclare classes, so the undoing diff leaves the classes that
+    /// this diff declares as they are; callers that track declared classes should remove them.
+    pub fn invert(&self) -> ReversibleStateDiff {
+        let mut diff = ThinStateDiff {
+            storage_diffs: self.previous_storage_values.clone(),
+            nonces: self.previous_nonces.clone(),
+            replaced_classes: self.previous_class_hashes.clone(),
+            ..ThinStateDiff::default()
+        };
+        diff.normalize();
+        let mut previous_class_hashes: IndexMap<ContractAddress, ClassHash> = self
+            .diff
+            .deployed_contracts
+            .iter()
+            .chain(self.diff.replaced_classes.iter())
+            .map(|(address, class_hash)| (*address, *class_hash))
+            .collect();
+        previous_class_hashes.sort_keys();
+        ReversibleStateDiff {
+            diff,
+            previous_storage_values: self.diff.storage_diffs.clone(),
+            previous_nonces: self.diff.nonces.clone(),
+            previous_class_hashes,
+        }
+    }
+}
+
 /// The sequential numbering of the states between blocks.
 // Example:
 // States: S0       S1       S2
@@ -151,10 +803,63 @@ impl_from_through_intermediate!(u128, StorageKey, u8, u16, u32, u64);
 #[derive(Debug, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
 pub struct ContractClass {
     pub sierra_program: Vec<StarkFelt>,
+    /// The version of the class hash scheme, [`CONTRACT_CLASS_VERSION`] if it is missing.
+    #[serde(default = "default_contract_class_version")]
+    pub contract_class_version: String,
     pub entry_point_by_type: HashMap<EntryPointType, Vec<EntryPoint>>,
     pub abi: String,
 }
 
+impl ContractClass {
+    /// Returns the hash of the class, which is the Poseidon hash of:
+    /// ["CONTRACT_CLASS_V" + contract_class_version,
+    ///  Poseidon(selector, function_idx, ...) of the external, L1 handler and constructor entry
+    ///  points, in this order,
+    ///  starknet_keccak(abi),
+    ///  Poseidon(sierra_program)].
+    ///
+    /// Returns an error if the version of the class is not [`CONTRACT_CLASS_VERSION`].
+    pub fn class_hash(&self) -> Result<ClassHash, StarknetApiError> {
+        self.class_hash_with_hasher::<Poseidon>()
+    }
+
+    /// Returns the hash of the class as in [`ContractClass::class_hash`], using `H` instead of the
+    /// Poseidon hash.
+    pub fn class_hash_with_hasher<H: StarkHasher>(&self) -> Result<ClassHash, StarknetApiError> {
+        if self.contract_class_version != CONTRACT_CLASS_VERSION {
+            return Err(StarknetApiError::UnsupportedContractClassVersion(
+                self.contract_class_version.clone(),
+            ));
+        }
+        let version = ascii_as_felt(&format!("CONTRACT_CLASS_V{}", self.contract_class_version))?;
+        let mut hash_chain = HashChain::new().chain(&version);
+        for entry_point_type in
+            [EntryPointType::External, EntryPointType::L1Handler, EntryPointType::Constructor]
+        {
+            let entry_points = self.entry_point_by_type.get(&entry_point_type);
+            let entry_points_hash = entry_points
+                .into_iter()
+                .flatten()
+                .fold(HashChain::new(), |hash_chain, entry_point| {
+                    hash_chain
+                        .chain(&entry_point.selector.0)
+                        .chain(&entry_point.function_idx.into())
+                })
+                .get_hash::<H>();
+            hash_chain = hash_chain.chain(&entry_points_hash);
+        }
+        let class_hash = hash_chain
+            .chain(&starknet_keccak(self.abi.as_bytes()))
+            .chain(&H::hash_array(&self.sierra_program))
+            .get_hash::<H>();
+        Ok(ClassHash(class_hash))
+    }
+}
+
+fn default_contract_class_version() -> String {
+    CONTRACT_CLASS_VERSION.to_owned()
+}
+
 #[derive(
     Debug, Default, Clone, Copy, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord,
 )]
@@ -183,3 +888,11 @@ pub struct EntryPoint {
     Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord,
 )]
 pub struct FunctionIndex(pub usize);
+
+impl From<FunctionIndex> for StarkFelt {
+    fn from(function_index: FunctionIndex) -> Self {
+        Self::from(
+            u64::try_from(function_index.0).expect("Expect a function index that fits in 64 bits."),
+        )
+    }
+}
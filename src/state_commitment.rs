@@ -0,0 +1,379 @@
+#[cfg(test)]
+#[path = "state_commitment_test.rs"]
+mod state_commitment_test;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::StarknetApiError;
+use crate::core::{ClassHash, CompiledClassHash, ContractAddress, GlobalRoot, Nonce};
+use crate::hash::{Pedersen, Poseidon, StarkFelt, StarkHash, StarkHasher, lazy_ascii_as_felt};
+use crate::patricia::{PatriciaTrie, ProofNode, invalid_proof, verify_proof};
+use crate::state::{StateDiff, StateUpdate, StorageKey};
+
+static STARKNET_STATE_V0: Lazy<StarkFelt> = lazy_ascii_as_felt!("STARKNET_STATE_V0");
+
+static CONTRACT_CLASS_LEAF_V0: Lazy<StarkFelt> = lazy_ascii_as_felt!("CONTRACT_CLASS_LEAF_V0");
+
+// The only version of the contract state hash.
+const CONTRACT_STATE_HASH_VERSION: StarkFelt = StarkFelt::ZERO;
+
+/// The Patricia-Merkle tries that commit to a Starknet state: a storage trie per contract, the
+/// contracts trie and the classes trie.
+///
+/// The tries are updated in place, so computing the global root after a change only hashes the
+/// nodes that the change affects.
+// Invariant: The leaves of the contracts trie are the hashes of the contracts.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct StateTries {
+    contracts: BTreeMap<ContractAddress, ContractState>,
+    contracts_trie: PatriciaTrie<Pedersen>,
+    classes: PatriciaTrie<Poseidon>,
+}
+
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+struct ContractState {
+    class_hash: ClassHash,
+    nonce: Nonce,
+    storage: PatriciaTrie<Pedersen>,
+}
+
+impl ContractState {
+    fn hash(&self) -> StarkHash {
+        calculate_contract_state_hash(&self.class_hash, &self.storage.root(), &self.nonce)
+    }
+}
+
+impl StateTries {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the value of a storage key of a contract. A zero value deletes the key.
+    pub fn set_storage(&mut self, address: ContractAddress, key: StorageKey, value: StarkFelt) {
+        self.contracts.entry(address).or_default().storage.insert(&key, value);
+        self.update_contract_leaf(address);
+    }
+
+    /// Sets the class of a contract, when it is deployed or its class is replaced.
+    pub fn set_class_hash(&mut self, address: ContractAddress, class_hash: ClassHash) {
+        self.contracts.entry(address).or_default().class_hash = class_hash;
+        self.update_contract_leaf(address);
+    }
+
+    pub fn set_nonce(&mut self, address: ContractAddress, nonce: Nonce) {
+        self.contracts.entry(address).or_default().nonce = nonce;
+        self.update_contract_leaf(address);
+    }
+
+    /// Sets the compiled class hash of a declared Cairo 1 class.
+    pub fn set_compiled_class_hash(
+        &mut self,
+        class_hash: ClassHash,
+        compiled_class_hash: CompiledClassHash,
+    ) {
+        self.classes
+            .insert(&class_key(class_hash), calculate_class_commitment_leaf(&compiled_class_hash));
+    }
+
+    /// Returns the global root of the state.
+    pub fn global_root(&self) -> GlobalRoot {
+        calculate_global_root(&self.contracts_trie.root(), &self.classes.root())
+    }
+
+    /// Applies the changes of a state diff. Deprecated classes are not committed to, so their
+    /// declarations leave the tries as they are.
+    pub fn apply_state_diff(&mut self, state_diff: &StateDiff) {
+        // The leaf of each changed contract is updated once, after all its changes.
+        let mut changed_contracts = BTreeSet::new();
+        for (address, class_hash) in
+            state_diff.deployed_contracts.iter().chain(state_diff.replaced_classes.iter())
+        {
+            self.contracts.entry(*address).or_default().class_hash = *class_hash;
+            changed_contracts.insert(*address);
+        }
+        for (address, nonce) in &state_diff.nonces {
+            self.contracts.entry(*address).or_default().nonce = *nonce;
+            changed_contracts.insert(*address);
+        }
+        for (address, storage_diff) in &state_diff.storage_diffs {
+            for (key, value) in storage_diff {
+                self.contracts.entry(*address).or_default().storage.insert(key, *value);
+                changed_contracts.insert(*address);
+            }
+        }
+        for address in changed_contracts {
+            self.update_contract_leaf(address);
+        }
+        for (class_hash, (compiled_class_hash, _)) in &state_diff.declared_classes {
+            self.set_compiled_class_hash(*class_hash, *compiled_class_hash);
+        }
+    }
+
+    /// Applies a state update, after checking that its old root is the root of the state and that
+    /// its diff yields its new root. The state does not change if either check fails.
+    pub fn apply_state_update(
+        &mut self,
+        state_update: &StateUpdate,
+    ) -> Result<(), StarknetApiError> {
+        let old_root = self.global_root();
+        if old_root != state_update.old_root {
+            return Err(StarknetApiError::GlobalRootMismatch {
+                expected: state_update.old_root,
+                calculated: old_root,
+            });
+        }
+        let previous_values = self.previous_values(&state_update.state_diff);
+        self.apply_state_diff(&state_update.state_diff);
+        let new_root = self.global_root();
+        if new_root != state_update.new_root {
+            self.revert(previous_values);
+            return Err(StarknetApiError::GlobalRootMismatch {
+                expected: state_update.new_root,
+                calculated: new_root,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns a proof of the value of a storage key of a contract in the state with the given
+    /// global root, in the shape of `starknet_getProof`. See [`verify_storage_proof`].
+    pub fn get_proof(
+        &self,
+        global_root: &GlobalRoot,
+        contract_address: &ContractAddress,
+        key: &StorageKey,
+    ) -> Result<StorageProof, StarknetApiError> {
+        let classes_root = self.classes.root();
+        let state_commitment = calculate_global_root(&self.contracts_trie.root(), &classes_root);
+        if state_commitment != *global_root {
+            return Err(StarknetApiError::UnknownGlobalRoot(*global_root));
+        }
+
+        let contract_data = self.contracts.get(contract_address).map(|contract| ContractData {
+            class_hash: contract.class_hash,
+            nonce: contract.nonce,
+            root: contract.storage.root(),
+            contract_state_hash_version: CONTRACT_STATE_HASH_VERSION,
+            storage_proofs: vec![contract.storage.get_proof(key)],
+        });
+        Ok(StorageProof {
+            state_commitment,
+            class_commitment: classes_root,
+            contract_proof: self.contracts_trie.get_proof(&StorageKey(contract_address.0)),
+            contract_data,
+        })
+    }
+
+    // Sets the leaf of a contract in the contracts trie to its hash, or deletes it if the contract
+    // is not in the state.
+    fn update_contract_leaf(&mut self, address: ContractAddress) {
+        let hash = self.contracts.get(&address).map_or(StarkFelt::ZERO, ContractState::hash);
+        self.contracts_trie.insert(&StorageKey(address.0), hash);
+    }
+
+    // Returns the values in the tries that a state diff overwrites.
+    fn previous_values(&self, state_diff: &StateDiff) -> PreviousValues {
+        let addresses = state_diff
+            .deployed_contracts
+            .keys()
+            .chain(state_diff.replaced_classes.keys())
+            .chain(state_diff.nonces.keys())
+            .chain(state_diff.storage_diffs.keys());
+        let contracts = addresses
+            .map(|address| {
+                let contract = self.contracts.get(address);
+                (*address, contract.map(|contract| (contract.class_hash, contract.nonce)))
+            })
+            .collect();
+        let storage = state_diff
+            .storage_diffs
+            .iter()
+            .flat_map(|(address, storage_diff)| {
+                storage_diff.keys().map(move |key| {
+                    let value = self
+                        .contracts
+                        .get(address)
+                        .map_or(StarkFelt::ZERO, |contract| contract.storage.get(key));
+                    (*address, *key, value)
+                })
+            })
+            .collect();
+        let classes = state_diff
+            .declared_classes
+            .keys()
+            .map(|class_hash| {
+                let key = class_key(*class_hash);
+                let value = self.classes.get(&key);
+                (key, value)
+            })
+            .collect();
+        PreviousValues { contracts, storage, classes }
+    }
+
+    // Restores the values that a state diff overwrote.
+    fn revert(&mut self, previous_values: PreviousValues) {
+        for (address, key, value) in previous_values.storage {
+            self.contracts.entry(address).or_default().storage.insert(&key, value);
+        }
+        for (address, class_hash_and_nonce) in previous_values.contracts {
+            match class_hash_and_nonce {
+                Some((class_hash, nonce)) => {
+                    let contract = self.contracts.entry(address).or_default();
+                    contract.class_hash = class_hash;
+                    contract.nonce = nonce;
+                }
+                None => {
+                    self.contracts.remove(&address);
+                }
+            }
+            self.update_contract_leaf(address);
+        }
+        for (key, value) in previous_values.classes {
+            self.classes.insert(&key, value);
+        }
+    }
+}
+
+// The values in the tries that a state diff overwrites, to revert it.
+struct PreviousValues {
+    // The class hash and nonce of each contract that the diff changes, or None if it is not in
+    // the state.
+    contracts: BTreeMap<ContractAddress, Option<(ClassHash, Nonce)>>,
+    storage: Vec<(ContractAddress, StorageKey, StarkFelt)>,
+    classes: Vec<(StorageKey, StarkHash)>,
+}
+
+fn class_key(class_hash: ClassHash) -> StorageKey {
+    StorageKey::try_from(class_hash.0)
+        .expect("A class hash is a hash and therefore a valid storage key.")
+}
+
+/// A proof of the value of a storage key of a contract, as returned by `starknet_getProof`.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub struct StorageProof {
+    pub state_commitment: GlobalRoot,
+    pub class_commitment: StarkHash,
+    /// The proof of the contract in the contracts trie.
+    pub contract_proof: Vec<ProofNode>,
+    /// The state of the contract, or None if it is not deployed.
+    pub contract_data: Option<ContractData>,
+}
+
+/// The state of a contract in a [`StorageProof`].
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub struct ContractData {
+    pub class_hash: ClassHash,
+    pub nonce: Nonce,
+    /// The root of the storage trie of the contract.
+    pub root: StarkHash,
+    pub contract_state_hash_version: StarkFelt,
+    /// The proofs of the requested keys in the storage trie of the contract.
+    pub storage_proofs: Vec<Vec<ProofNode>>,
+}
+
+/// Verifies a proof of a storage key of a contract against a trusted global root, and returns the
+/// value of the key. The value of a key that is not set, or of a contract that is not deployed, is
+/// zero.
+pub fn verify_storage_proof(
+    trusted_root: &GlobalRoot,
+    contract_address: &ContractAddress,
+    key: &StorageKey,
+    proof: &StorageProof,
+) -> Result<StarkFelt, StarknetApiError> {
+    if proof.state_commitment != *trusted_root {
+        return Err(invalid_proof("the state commitment is not the trusted root"));
+    }
+    let contracts_root =
+        proof.contract_proof.first().map_or(StarkFelt::ZERO, |node| node.hash::<Pedersen>());
+    if calculate_global_root(&contracts_root, &proof.class_commitment) != *trusted_root {
+        return Err(invalid_proof(
+            "the contracts and classes roots do not hash to the trusted root",
+        ));
+    }
+
+    let proven_contract_state_hash = verify_proof::<Pedersen>(
+        &contracts_root,
+        &StorageKey(contract_address.0),
+        &proof.contract_proof,
+    )?;
+    if proven_contract_state_hash == StarkFelt::ZERO {
+        return Ok(StarkFelt::ZERO);
+    }
+
+    let Some(contract_data) = &proof.contract_data else {
+        return Err(invalid_proof("the contract is deployed but its data is missing"));
+    };
+    if contract_data.contract_state_hash_version != CONTRACT_STATE_HASH_VERSION {
+        return Err(invalid_proof("the contract state hash version is unknown"));
+    }
+    let calculated_hash = calculate_contract_state_hash(
+        &contract_data.class_hash,
+        &contract_data.root,
+        &contract_data.nonce,
+    );
+    if calculated_hash != proven_contract_state_hash {
+        return Err(invalid_proof("the contract data does not hash to the proven contract state"));
+    }
+    let [storage_proof] = contract_data.storage_proofs.as_slice() else {
+        return Err(invalid_proof("expected a single storage proof"));
+    };
+    verify_proof::<Pedersen>(&contract_data.root, key, storage_proof)
+}
+
+/// Calculates the hash of the state of a contract, which is its leaf in the contracts trie:
+/// H(H(H(class_hash, storage_root), nonce), 0), where H is the Pedersen hash.
+pub fn calculate_contract_state_hash(
+    class_hash: &ClassHash,
+    storage_root: &StarkHash,
+    nonce: &Nonce,
+) -> StarkHash {
+    calculate_contract_state_hash_with_hasher::<Pedersen>(class_hash, storage_root, nonce)
+}
+
+/// Calculates the hash of the state of a contract as in [`calculate_contract_state_hash`], using
+/// `H` instead of the Pedersen hash.
+pub fn calculate_contract_state_hash_with_hasher<H: StarkHasher>(
+    class_hash: &ClassHash,
+    storage_root: &StarkHash,
+    nonce: &Nonce,
+) -> StarkHash {
+    let hash = H::hash(&class_hash.0, storage_root);
+    let hash = H::hash(&hash, &nonce.0);
+    H::hash(&hash, &CONTRACT_STATE_HASH_VERSION)
+}
+
+/// Calculates the leaf of a class in the classes trie:
+/// Poseidon("CONTRACT_CLASS_LEAF_V0", compiled_class_hash).
+pub fn calculate_class_commitment_leaf(compiled_class_hash: &CompiledClassHash) -> StarkHash {
+    calculate_class_commitment_leaf_with_hasher::<Poseidon>(compiled_class_hash)
+}
+
+/// Calculates the leaf of a class as in [`calculate_class_commitment_leaf`], using `H` instead of
+/// the Poseidon hash.
+pub fn calculate_class_commitment_leaf_with_hasher<H: StarkHasher>(
+    compiled_class_hash: &CompiledClassHash,
+) -> StarkHash {
+    H::hash_array(&[*CONTRACT_CLASS_LEAF_V0, compiled_class_hash.0])
+}
+
+/// Calculates the global root of the state from the roots of the contracts and classes tries:
+/// Poseidon("STARKNET_STATE_V0", contracts_root, classes_root). While no Cairo 1 class is declared
+/// the classes root is zero, and the global root is the contracts root.
+pub fn calculate_global_root(contracts_root: &StarkHash, classes_root: &StarkHash) -> GlobalRoot {
+    calculate_global_root_with_hasher::<Poseidon>(contracts_root, classes_root)
+}
+
+/// Calculates the global root as in [`calculate_global_root`], using `H` instead of the Poseidon
+/// hash.
+pub fn calculate_global_root_with_hasher<H: StarkHasher>(
+    contracts_root: &StarkHash,
+    classes_root: &StarkHash,
+) -> GlobalRoot {
+    if *classes_root == StarkFelt::ZERO {
+        return GlobalRoot(*contracts_root);
+    }
+    GlobalRoot(H::hash_array(&[*STARKNET_STATE_V0, *contracts_root, *classes_root]))
+}
@@ -0,0 +1,314 @@
+use assert_matches::assert_matches;
+use indexmap::indexmap;
+
+use crate::block::BlockHash;
+use crate::core::{ClassHash, CompiledClassHash, ContractAddress, GlobalRoot, Nonce, PatriciaKey};
+use crate::hash::{
+    Pedersen, Poseidon, StarkFelt, StarkHash, ascii_as_felt, pedersen_hash, pedersen_hash_array,
+    poseidon_hash, poseidon_hash_many,
+};
+use crate::patricia::{PatriciaTrie, ProofNode};
+use crate::state::{ContractClass, StateDiff, StateUpdate, StorageKey};
+use crate::state_commitment::{
+    StateTries, calculate_class_commitment_leaf, calculate_class_commitment_leaf_with_hasher,
+    calculate_contract_state_hash, calculate_contract_state_hash_with_hasher,
+    calculate_global_root, calculate_global_root_with_hasher, verify_storage_proof,
+};
+use crate::{StarknetApiError, class_hash, contract_address, patricia_key, stark_felt};
+
+fn get_state() -> StateTries {
+    let mut state = StateTries::new();
+    for i in 1..=5_u64 {
+        let address = contract_address!(i);
+        state.set_class_hash(address, class_hash!(i + 10));
+        state.set_nonce(address, Nonce(stark_felt!(i)));
+        for j in 1..=5_u64 {
+            state.set_storage(address, StorageKey::from(j * 7), stark_felt!(i * j));
+        }
+    }
+    state.set_compiled_class_hash(class_hash!("0x11"), CompiledClassHash(stark_felt!("0x12")));
+    state
+}
+
+#[test]
+fn storage_proof_of_value() {
+    let state = get_state();
+    let root = state.global_root();
+    let address = contract_address!("0x3");
+    let key = StorageKey::from(14_u64);
+
+    let proof = state.get_proof(&root, &address, &key).unwrap();
+    assert_eq!(verify_storage_proof(&root, &address, &key, &proof).unwrap(), stark_felt!(6_u64));
+}
+
+#[test]
+fn storage_proof_of_absence() {
+    let state = get_state();
+    let root = state.global_root();
+
+    // A key that is not set.
+    let address = contract_address!("0x3");
+    let key = StorageKey::from(15_u64);
+    let proof = state.get_proof(&root, &address, &key).unwrap();
+    assert_eq!(verify_storage_proof(&root, &address, &key, &proof).unwrap(), StarkFelt::ZERO);
+
+    // A contract that is not deployed.
+    let address = contract_address!("0x6");
+    let proof = state.get_proof(&root, &address, &key).unwrap();
+    assert!(proof.contract_data.is_none());
+    assert_eq!(verify_storage_proof(&root, &address, &key, &proof).unwrap(), StarkFelt::ZERO);
+}
+
+#[test]
+fn get_proof_of_unknown_root() {
+    let state = get_state();
+    let root = GlobalRoot(stark_felt!("0x1"));
+    assert_matches!(
+        state.get_proof(&root, &contract_address!("0x1"), &StorageKey::from(7_u64)),
+        Err(StarknetApiError::UnknownGlobalRoot(unknown_root)) if unknown_root == root
+    );
+}
+
+#[test]
+fn invalid_storage_proofs() {
+    let state = get_state();
+    let root = state.global_root();
+    let address = contract_address!("0x3");
+    let key = StorageKey::from(14_u64);
+    let proof = state.get_proof(&root, &address, &key).unwrap();
+
+    // Against another root.
+    let other_root = GlobalRoot(stark_felt!("0x1"));
+    assert_matches!(
+        verify_storage_proof(&other_root, &address, &key, &proof),
+        Err(StarknetApiError::InvalidProof(_))
+    );
+
+    // With tampered contract data.
+    let mut tampered_proof = proof.clone();
+    tampered_proof.contract_data.as_mut().unwrap().nonce = Nonce(stark_felt!("0x100"));
+    assert_matches!(
+        verify_storage_proof(&root, &address, &key, &tampered_proof),
+        Err(StarknetApiError::InvalidProof(_))
+    );
+
+    // With a tampered storage proof.
+    let mut tampered_proof = proof.clone();
+    let storage_proof = &mut tampered_proof.contract_data.as_mut().unwrap().storage_proofs[0];
+    match storage_proof.last_mut().unwrap() {
+        ProofNode::Binary { left, .. } => *left = stark_felt!("0x100"),
+        ProofNode::Edge { child, .. } => *child = stark_felt!("0x100"),
+    }
+    assert_matches!(
+        verify_storage_proof(&root, &address, &key, &tampered_proof),
+        Err(StarknetApiError::InvalidProof(_))
+    );
+
+    // Without the contract data of a deployed contract.
+    let mut tampered_proof = proof;
+    tampered_proof.contract_data = None;
+    assert_matches!(
+        verify_storage_proof(&root, &address, &key, &tampered_proof),
+        Err(StarknetApiError::InvalidProof(_))
+    );
+}
+
+#[test]
+fn contract_state_hash() {
+    let class_hash = class_hash!("0x1");
+    let storage_root = stark_felt!("0x2");
+    let nonce = Nonce(stark_felt!("0x3"));
+
+    let hash = pedersen_hash(&class_hash.0, &storage_root);
+    let hash = pedersen_hash(&hash, &nonce.0);
+    let expected = pedersen_hash(&hash, &StarkFelt::ZERO);
+    assert_eq!(calculate_contract_state_hash(&class_hash, &storage_root, &nonce), expected);
+}
+
+#[test]
+fn class_commitment_leaf() {
+    let compiled_class_hash = CompiledClassHash(stark_felt!("0x1"));
+    let expected =
+        poseidon_hash_many(&[ascii_as_felt("CONTRACT_CLASS_LEAF_V0").unwrap(), stark_felt!("0x1")]);
+    assert_eq!(calculate_class_commitment_leaf(&compiled_class_hash), expected);
+}
+
+#[test]
+fn global_root() {
+    let contracts_root = stark_felt!("0x1");
+    assert_eq!(
+        calculate_global_root(&contracts_root, &StarkFelt::ZERO),
+        GlobalRoot(contracts_root)
+    );
+
+    let classes_root = stark_felt!("0x2");
+    let expected = poseidon_hash_many(&[
+        ascii_as_felt("STARKNET_STATE_V0").unwrap(),
+        contracts_root,
+        classes_root,
+    ]);
+    assert_eq!(calculate_global_root(&contracts_root, &classes_root), GlobalRoot(expected));
+}
+
+#[test]
+fn state_commitment_with_hasher() {
+    let hash = poseidon_hash(&stark_felt!("0x1"), &stark_felt!("0x2"));
+    let hash = poseidon_hash(&hash, &stark_felt!("0x3"));
+    assert_eq!(
+        calculate_contract_state_hash_with_hasher::<Poseidon>(
+            &class_hash!("0x1"),
+            &stark_felt!("0x2"),
+            &Nonce(stark_felt!("0x3"))
+        ),
+        poseidon_hash(&hash, &StarkFelt::ZERO)
+    );
+
+    let leaf_prefix = ascii_as_felt("CONTRACT_CLASS_LEAF_V0").unwrap();
+    assert_eq!(
+        calculate_class_commitment_leaf_with_hasher::<Pedersen>(&CompiledClassHash(stark_felt!(
+            "0x1"
+        ))),
+        pedersen_hash_array(&[leaf_prefix, stark_felt!("0x1")])
+    );
+
+    let state_prefix = ascii_as_felt("STARKNET_STATE_V0").unwrap();
+    assert_eq!(
+        calculate_global_root_with_hasher::<Pedersen>(&stark_felt!("0x1"), &stark_felt!("0x2")),
+        GlobalRoot(pedersen_hash_array(&[state_prefix, stark_felt!("0x1"), stark_felt!("0x2")]))
+    );
+}
+
+#[test]
+fn state_tries_global_root() {
+    let address = contract_address!("0x1");
+    let key = StorageKey::from(2_u64);
+    let mut state = StateTries::new();
+    state.set_class_hash(address, class_hash!("0x3"));
+    state.set_nonce(address, Nonce(stark_felt!("0x4")));
+    state.set_storage(address, key, stark_felt!("0x5"));
+
+    let mut storage_trie = PatriciaTrie::<Pedersen>::new();
+    storage_trie.insert(&key, stark_felt!("0x5"));
+    let mut contracts_trie = PatriciaTrie::<Pedersen>::new();
+    contracts_trie.insert(
+        &StorageKey(address.0),
+        calculate_contract_state_hash(
+            &class_hash!("0x3"),
+            &storage_trie.root(),
+            &Nonce(stark_felt!("0x4")),
+        ),
+    );
+    assert_eq!(state.global_root(), GlobalRoot(contracts_trie.root()));
+}
+
+fn get_state_diff() -> StateDiff {
+    StateDiff {
+        deployed_contracts: indexmap! { contract_address!("0x1") => class_hash!("0x10") },
+        storage_diffs: indexmap! {
+            contract_address!("0x1") => indexmap! { StorageKey::from(2_u64) => stark_felt!("0x3") },
+            contract_address!("0x4") => indexmap! { StorageKey::from(5_u64) => stark_felt!("0x6") },
+        },
+        declared_classes: indexmap! {
+            class_hash!("0x11") => (CompiledClassHash(stark_felt!("0x12")), ContractClass::default()),
+        },
+        nonces: indexmap! { contract_address!("0x1") => Nonce(stark_felt!("0x1")) },
+        ..StateDiff::default()
+    }
+}
+
+#[test]
+fn apply_state_diff() {
+    let mut state = StateTries::new();
+    state.apply_state_diff(&get_state_diff());
+
+    let mut expected_state = StateTries::new();
+    expected_state.set_class_hash(contract_address!("0x1"), class_hash!("0x10"));
+    expected_state.set_nonce(contract_address!("0x1"), Nonce(stark_felt!("0x1")));
+    expected_state.set_storage(
+        contract_address!("0x1"),
+        StorageKey::from(2_u64),
+        stark_felt!("0x3"),
+    );
+    expected_state.set_storage(
+        contract_address!("0x4"),
+        StorageKey::from(5_u64),
+        stark_felt!("0x6"),
+    );
+    expected_state
+        .set_compiled_class_hash(class_hash!("0x11"), CompiledClassHash(stark_felt!("0x12")));
+    assert_eq!(state, expected_state);
+    assert_eq!(state.global_root(), expected_state.global_root());
+}
+
+#[test]
+fn apply_state_update() {
+    let state_diff = get_state_diff();
+    let mut expected_state = StateTries::new();
+    expected_state.apply_state_diff(&state_diff);
+    let new_root = expected_state.global_root();
+
+    let mut state = StateTries::new();
+    let mut state_update = StateUpdate {
+        block_hash: BlockHash::default(),
+        new_root: GlobalRoot(stark_felt!("0x1")),
+        old_root: GlobalRoot::default(),
+        state_diff,
+    };
+    assert_matches!(
+        state.apply_state_update(&state_update),
+        Err(StarknetApiError::GlobalRootMismatch { expected, calculated })
+        if expected == GlobalRoot(stark_felt!("0x1")) && calculated == new_root
+    );
+    assert_eq!(state, StateTries::new());
+
+    state_update.new_root = new_root;
+    state.apply_state_update(&state_update).unwrap();
+    assert_eq!(state.global_root(), new_root);
+
+    // The old root of the update is no longer the root of the state.
+    assert_matches!(
+        state.apply_state_update(&state_update),
+        Err(StarknetApiError::GlobalRootMismatch { expected, calculated })
+        if expected == GlobalRoot::default() && calculated == new_root
+    );
+}
+
+#[test]
+fn apply_state_update_with_wrong_new_root_leaves_state() {
+    let mut state = get_state();
+    let old_root = state.global_root();
+    let state_update = StateUpdate {
+        block_hash: BlockHash::default(),
+        new_root: GlobalRoot(stark_felt!("0x1")),
+        old_root,
+        state_diff: StateDiff {
+            deployed_contracts: indexmap! { contract_address!(6_u64) => class_hash!("0x16") },
+            storage_diffs: indexmap! {
+                contract_address!(1_u64) => indexmap! {
+                    StorageKey::from(7_u64) => StarkFelt::ZERO,
+                    StorageKey::from(8_u64) => stark_felt!("0x8"),
+                },
+                contract_address!(6_u64) => indexmap! {
+                    StorageKey::from(1_u64) => stark_felt!("0x9"),
+                },
+            },
+            declared_classes: indexmap! {
+                class_hash!("0x11") =>
+                    (CompiledClassHash(stark_felt!("0x13")), ContractClass::default()),
+                class_hash!("0x21") =>
+                    (CompiledClassHash(stark_felt!("0x22")), ContractClass::default()),
+            },
+            nonces: indexmap! { contract_address!(2_u64) => Nonce(stark_felt!("0x7")) },
+            replaced_classes: indexmap! { contract_address!(3_u64) => class_hash!("0x17") },
+            ..StateDiff::default()
+        },
+    };
+
+    assert_matches!(
+        state.apply_state_update(&state_update),
+        Err(StarknetApiError::GlobalRootMismatch { expected, .. })
+        if expected == GlobalRoot(stark_felt!("0x1"))
+    );
+    assert_eq!(state, get_state());
+    assert_eq!(state.global_root(), old_root);
+}
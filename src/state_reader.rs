@@ -0,0 +1,123 @@
+#[cfg(test)]
+#[path = "state_reader_test.rs"]
+mod state_reader_test;
+
+use std::collections::HashMap;
+
+use crate::StarknetApiError;
+use crate::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use crate::deprecated_contract_class::ContractClass as DeprecatedContractClass;
+use crate::hash::StarkFelt;
+use crate::state::{ContractClass, StateDiff, StorageKey};
+
+/// A class declared in the state: a Cairo 1 class or a deprecated (Cairo 0) one.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum DeclaredClass {
+    Cairo1(ContractClass),
+    Deprecated(Box<DeprecatedContractClass>),
+}
+
+/// Read access to a Starknet state.
+///
+/// Entries that were never set read as zero, except for classes, which are an error to read if
+/// they are not declared.
+pub trait StateReader {
+    /// Returns the value of a storage key of a contract.
+    fn get_storage_at(
+        &self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> Result<StarkFelt, StarknetApiError>;
+
+    /// Returns the nonce of a contract.
+    fn get_nonce_at(&self, contract_address: ContractAddress) -> Result<Nonce, StarknetApiError>;
+
+    /// Returns the class hash of a contract; zero if it is not deployed.
+    fn get_class_hash_at(
+        &self,
+        contract_address: ContractAddress,
+    ) -> Result<ClassHash, StarknetApiError>;
+
+    /// Returns the compiled class hash of a Cairo 1 class; zero if it is not declared.
+    fn get_compiled_class_hash(
+        &self,
+        class_hash: ClassHash,
+    ) -> Result<CompiledClassHash, StarknetApiError>;
+
+    /// Returns a declared class.
+    fn get_class(&self, class_hash: ClassHash) -> Result<DeclaredClass, StarknetApiError>;
+}
+
+/// A [`StateReader`] that holds the whole state in memory, built by applying state diffs.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct InMemoryState {
+    storage: HashMap<(ContractAddress, StorageKey), StarkFelt>,
+    nonces: HashMap<ContractAddress, Nonce>,
+    class_hashes: HashMap<ContractAddress, ClassHash>,
+    compiled_class_hashes: HashMap<ClassHash, CompiledClassHash>,
+    classes: HashMap<ClassHash, DeclaredClass>,
+}
+
+impl InMemoryState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies the changes of a state diff.
+    pub fn apply(&mut self, state_diff: &StateDiff) {
+        for (address, class_hash) in
+            state_diff.deployed_contracts.iter().chain(state_diff.replaced_classes.iter())
+        {
+            self.class_hashes.insert(*address, *class_hash);
+        }
+        for (address, diff) in &state_diff.storage_diffs {
+            for (key, value) in diff {
+                if *value == StarkFelt::ZERO {
+                    self.storage.remove(&(*address, *key));
+                } else {
+                    self.storage.insert((*address, *key), *value);
+                }
+            }
+        }
+        self.nonces.extend(state_diff.nonces.iter().map(|(address, nonce)| (*address, *nonce)));
+        for (class_hash, (compiled_class_hash, class)) in &state_diff.declared_classes {
+            self.compiled_class_hashes.insert(*class_hash, *compiled_class_hash);
+            self.classes.insert(*class_hash, DeclaredClass::Cairo1(class.clone()));
+        }
+        for (class_hash, class) in &state_diff.deprecated_declared_classes {
+            self.classes.insert(*class_hash, DeclaredClass::Deprecated(Box::new(class.clone())));
+        }
+    }
+}
+
+impl StateReader for InMemoryState {
+    fn get_storage_at(
+        &self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> Result<StarkFelt, StarknetApiError> {
+        Ok(self.storage.get(&(contract_address, key)).copied().unwrap_or_default())
+    }
+
+    fn get_nonce_at(&self, contract_address: ContractAddress) -> Result<Nonce, StarknetApiError> {
+        Ok(self.nonces.get(&contract_address).copied().unwrap_or_default())
+    }
+
+    fn get_class_hash_at(
+        &self,
+        contract_address: ContractAddress,
+    ) -> Result<ClassHash, StarknetApiError> {
+        Ok(self.class_hashes.get(&contract_address).copied().unwrap_or_default())
+    }
+
+    fn get_compiled_class_hash(
+        &self,
+        class_hash: ClassHash,
+    ) -> Result<CompiledClassHash, StarknetApiError> {
+        Ok(self.compiled_class_hashes.get(&class_hash).copied().unwrap_or_default())
+    }
+
+    fn get_class(&self, class_hash: ClassHash) -> Result<DeclaredClass, StarknetApiError> {
+        self.classes.get(&class_hash).cloned().ok_or(StarknetApiError::UndeclaredClass(class_hash))
+    }
+}
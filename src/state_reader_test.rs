@@ -0,0 +1,105 @@
+use assert_matches::assert_matches;
+use indexmap::indexmap;
+
+use crate::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce, PatriciaKey};
+use crate::deprecated_contract_class::ContractClass as DeprecatedContractClass;
+use crate::hash::{StarkFelt, StarkHash};
+use crate::state::{ContractClass, ReversibleStateDiff, StateDiff, StorageKey, ThinStateDiff};
+use crate::state_reader::{DeclaredClass, InMemoryState, StateReader};
+use crate::{StarknetApiError, class_hash, contract_address, patricia_key, stark_felt};
+
+fn get_state_diff() -> StateDiff {
+    StateDiff {
+        deployed_contracts: indexmap! { contract_address!(1_u64) => class_hash!(1_u64) },
+        storage_diffs: indexmap! {
+            contract_address!(1_u64) => indexmap! {
+                StorageKey::from(1_u64) => stark_felt!(1_u64),
+                StorageKey::from(2_u64) => stark_felt!(2_u64),
+            },
+        },
+        declared_classes: indexmap! {
+            class_hash!(2_u64) => (CompiledClassHash(stark_felt!(3_u64)), ContractClass::default()),
+        },
+        deprecated_declared_classes: indexmap! {
+            class_hash!(1_u64) => DeprecatedContractClass::default(),
+        },
+        nonces: indexmap! { contract_address!(1_u64) => Nonce(stark_felt!(1_u64)) },
+        replaced_classes: indexmap! {},
+    }
+}
+
+#[test]
+fn in_memory_state_apply() {
+    let mut state = InMemoryState::new();
+    state.apply(&get_state_diff());
+    state.apply(&StateDiff {
+        storage_diffs: indexmap! {
+            contract_address!(1_u64) => indexmap! { StorageKey::from(1_u64) => StarkFelt::ZERO },
+        },
+        nonces: indexmap! { contract_address!(1_u64) => Nonce(stark_felt!(2_u64)) },
+        replaced_classes: indexmap! { contract_address!(1_u64) => class_hash!(2_u64) },
+        ..StateDiff::default()
+    });
+
+    let address = contract_address!(1_u64);
+    assert_eq!(state.get_storage_at(address, StorageKey::from(1_u64)).unwrap(), StarkFelt::ZERO);
+    assert_eq!(state.get_storage_at(address, StorageKey::from(2_u64)).unwrap(), stark_felt!(2_u64));
+    assert_eq!(state.get_nonce_at(address).unwrap(), Nonce(stark_felt!(2_u64)));
+    assert_eq!(state.get_class_hash_at(address).unwrap(), class_hash!(2_u64));
+    assert_eq!(
+        state.get_compiled_class_hash(class_hash!(2_u64)).unwrap(),
+        CompiledClassHash(stark_felt!(3_u64))
+    );
+    assert_eq!(
+        state.get_class(class_hash!(1_u64)).unwrap(),
+        DeclaredClass::Deprecated(Box::default())
+    );
+    assert_eq!(
+        state.get_class(class_hash!(2_u64)).unwrap(),
+        DeclaredClass::Cairo1(ContractClass::default())
+    );
+
+    // Entries that were never set read as zero.
+    let other_address = contract_address!(2_u64);
+    assert_eq!(state.get_class_hash_at(other_address).unwrap(), ClassHash::default());
+    assert_eq!(state.get_nonce_at(other_address).unwrap(), Nonce::default());
+    assert_eq!(
+        state.get_compiled_class_hash(class_hash!(1_u64)).unwrap(),
+        CompiledClassHash::default()
+    );
+}
+
+#[test]
+fn in_memory_state_undeclared_class() {
+    let state = InMemoryState::new();
+    assert_matches!(
+        state.get_class(class_hash!(1_u64)),
+        Err(StarknetApiError::UndeclaredClass(class_hash)) if class_hash == class_hash!(1_u64)
+    );
+}
+
+#[test]
+fn reversible_state_diff_from_state_reader() {
+    let mut state = InMemoryState::new();
+    state.apply(&get_state_diff());
+    let diff = ThinStateDiff {
+        storage_diffs: indexmap! {
+            contract_address!(1_u64) => indexmap! { StorageKey::from(1_u64) => stark_felt!(5_u64) },
+        },
+        nonces: indexmap! { contract_address!(1_u64) => Nonce(stark_felt!(2_u64)) },
+        replaced_classes: indexmap! { contract_address!(1_u64) => class_hash!(2_u64) },
+        ..ThinStateDiff::default()
+    };
+
+    let reversible_diff = ReversibleStateDiff::from_state_reader(diff.clone(), &state).unwrap();
+    let expected = ReversibleStateDiff::new(
+        diff,
+        indexmap! {
+            contract_address!(1_u64) => indexmap! { StorageKey::from(1_u64) => stark_felt!(1_u64) },
+        },
+        indexmap! { contract_address!(1_u64) => Nonce(stark_felt!(1_u64)) },
+        indexmap! { contract_address!(1_u64) => class_hash!(1_u64) },
+    )
+    .unwrap();
+    assert_eq!(reversible_diff, expected);
+}
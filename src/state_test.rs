@@ -1,8 +1,25 @@
 use std::collections::HashMap;
 
+use assert_matches::assert_matches;
+use indexmap::{IndexMap, indexmap};
 use serde_json::json;
 
-use crate::deprecated_contract_class::EntryPointOffset;
+use crate::block_commitment::StateDiffCommitment;
+use crate::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce, PatriciaKey};
+use crate::data_availability::{
+    BYTES_PER_FIELD_ELEMENT, L1DataAvailabilityMode, felts_to_calldata,
+};
+use crate::deprecated_contract_class::{
+    ContractClass as DeprecatedContractClass, EntryPointOffset,
+};
+use crate::hash::{Pedersen, Poseidon, StarkFelt, StarkHash, ascii_as_felt, poseidon_hash_many};
+use crate::state::{
+    CONTRACT_CLASS_VERSION, ContractClass, ReversibleStateDiff, StateDiff, StateDiffError,
+    StorageKey, ThinStateDiff,
+};
+use crate::state_reader::InMemoryState;
+use crate::test_utils::get_minimal_contract_class;
+use crate::{StarknetApiError, class_hash, contract_address, patricia_key, stark_felt};
 
 #[test]
 fn entry_point_offset_from_json_str() {
@@ -22,3 +39,495 @@ fn entry_point_offset_into_json_str() {
     let offset = EntryPointOffset(123);
     assert_eq!(json!(offset), json!(format!("{:#x}", offset.0)));
 }
+
+#[test]
+fn thin_state_diff_commitment() {
+    let state_diff = ThinStateDiff {
+        deployed_contracts: indexmap! {
+            contract_address!(12_u64) => class_hash!(13_u64),
+            contract_address!(0_u64) => class_hash!(1_u64),
+        },
+        storage_diffs: indexmap! {
+            contract_address!(2_u64) => indexmap! {
+                StorageKey::from(14_u64) => stark_felt!(15_u64),
+                StorageKey::from(3_u64) => stark_felt!(4_u64),
+            },
+            // Contracts without storage diffs are skipped.
+            contract_address!(1_u64) => indexmap! {},
+        },
+        declared_classes: indexmap! { class_hash!(5_u64) => CompiledClassHash(stark_felt!(6_u64)) },
+        deprecated_declared_classes: vec![class_hash!(16_u64), class_hash!(7_u64)],
+        nonces: indexmap! { contract_address!(8_u64) => Nonce(stark_felt!(9_u64)) },
+        replaced_classes: indexmap! { contract_address!(10_u64) => class_hash!(11_u64) },
+    };
+    let expected_commitment = poseidon_hash_many(&[
+        ascii_as_felt("STARKNET_STATE_DIFF0").unwrap(),
+        // Deployed and replaced contracts.
+        stark_felt!(3_u64),
+        stark_felt!(0_u64),
+        stark_felt!(1_u64),
+        stark_felt!(10_u64),
+        stark_felt!(11_u64),
+        stark_felt!(12_u64),
+        stark_felt!(13_u64),
+        // Declared classes.
+        stark_felt!(1_u64),
+        stark_felt!(5_u64),
+        stark_felt!(6_u64),
+        // Deprecated declared classes.
+        stark_felt!(2_u64),
+        stark_felt!(7_u64),
+        stark_felt!(16_u64),
+        // The data availability mode.
+        stark_felt!(1_u64),
+        stark_felt!(0_u64),
+        // Storage diffs.
+        stark_felt!(1_u64),
+        stark_felt!(2_u64),
+        stark_felt!(2_u64),
+        stark_felt!(3_u64),
+        stark_felt!(4_u64),
+        stark_felt!(14_u64),
+        stark_felt!(15_u64),
+        // Nonces.
+        stark_felt!(1_u64),
+        stark_felt!(8_u64),
+        stark_felt!(9_u64),
+    ]);
+    assert_eq!(state_diff.commitment(), StateDiffCommitment(expected_commitment));
+    assert_eq!(state_diff.len(), 9);
+    assert!(!state_diff.is_empty());
+    assert!(ThinStateDiff::default().is_empty());
+}
+
+#[test]
+fn state_diff_new_normalizes() {
+    let diff = StateDiff::new(
+        indexmap! {
+            contract_address!(2_u64) => class_hash!(3_u64),
+            contract_address!(1_u64) => class_hash!(4_u64),
+        },
+        indexmap! {
+            contract_address!(5_u64) => indexmap! {
+                StorageKey::from(7_u64) => stark_felt!(1_u64),
+                StorageKey::from(6_u64) => stark_felt!(1_u64),
+            },
+            contract_address!(2_u64) => indexmap! {},
+        },
+        IndexMap::new(),
+        IndexMap::new(),
+        indexmap! {
+            contract_address!(9_u64) => Nonce(stark_felt!(1_u64)),
+            contract_address!(8_u64) => Nonce(stark_felt!(1_u64)),
+        },
+        IndexMap::new(),
+    )
+    .unwrap();
+
+    let deployed_contracts: Vec<_> = diff.deployed_contracts.keys().copied().collect();
+    assert_eq!(deployed_contracts, vec![contract_address!(1_u64), contract_address!(2_u64)]);
+    let storage_contracts: Vec<_> = diff.storage_diffs.keys().copied().collect();
+    assert_eq!(storage_contracts, vec![contract_address!(2_u64), contract_address!(5_u64)]);
+    let storage_keys: Vec<_> =
+        diff.storage_diffs[&contract_address!(5_u64)].keys().copied().collect();
+    assert_eq!(storage_keys, vec![StorageKey::from(6_u64), StorageKey::from(7_u64)]);
+    let nonces: Vec<_> = diff.nonces.keys().copied().collect();
+    assert_eq!(nonces, vec![contract_address!(8_u64), contract_address!(9_u64)]);
+    diff.validate().unwrap();
+}
+
+#[test]
+fn state_diff_validate_unsorted() {
+    let diff = StateDiff {
+        nonces: indexmap! {
+            contract_address!(9_u64) => Nonce(stark_felt!(1_u64)),
+            contract_address!(8_u64) => Nonce(stark_felt!(1_u64)),
+        },
+        ..StateDiff::default()
+    };
+    assert_matches!(
+        diff.validate(),
+        Err(StarknetApiError::StateDiff(StateDiffError::UnsortedKeys("nonces")))
+    );
+}
+
+#[test]
+fn state_diff_new_rejects_overlapping_classes() {
+    let result = StateDiff::new(
+        IndexMap::new(),
+        IndexMap::new(),
+        indexmap! {
+            class_hash!(1_u64) => (CompiledClassHash(stark_felt!(2_u64)), ContractClass::default()),
+        },
+        indexmap! { class_hash!(1_u64) => DeprecatedContractClass::default() },
+        IndexMap::new(),
+        IndexMap::new(),
+    );
+    assert_matches!(
+        result,
+        Err(StarknetApiError::StateDiff(StateDiffError::OverlappingDeclaredClass(class_hash)))
+        if class_hash == class_hash!(1_u64)
+    );
+}
+
+#[test]
+fn thin_state_diff_new_rejects_duplicate_classes() {
+    let result = ThinStateDiff::new(
+        IndexMap::new(),
+        IndexMap::new(),
+        IndexMap::new(),
+        vec![class_hash!(1_u64), class_hash!(2_u64), class_hash!(1_u64)],
+        IndexMap::new(),
+        IndexMap::new(),
+    );
+    assert_matches!(
+        result,
+        Err(StarknetApiError::StateDiff(StateDiffError::DuplicateDeclaredClass(class_hash)))
+        if class_hash == class_hash!(1_u64)
+    );
+}
+
+#[test]
+fn thin_state_diff_new_rejects_out_of_range_values() {
+    let out_of_range_value = StarkFelt::new_unchecked([0xff; 32]);
+    let result = ThinStateDiff::new(
+        IndexMap::new(),
+        indexmap! {
+            contract_address!(1_u64) => indexmap! { StorageKey::from(2_u64) => out_of_range_value },
+        },
+        IndexMap::new(),
+        vec![],
+        IndexMap::new(),
+        IndexMap::new(),
+    );
+    assert_matches!(
+        result,
+        Err(StarknetApiError::StateDiff(StateDiffError::OutOfRangeValue {
+            field: "storage_diffs",
+            value,
+        }))
+        if value == out_of_range_value
+    );
+}
+
+#[test]
+fn thin_state_diff_squash() {
+    let first = ThinStateDiff {
+        deployed_contracts: indexmap! { contract_address!(2_u64) => class_hash!(1_u64) },
+        storage_diffs: indexmap! {
+            contract_address!(2_u64) => indexmap! {
+                StorageKey::from(1_u64) => stark_felt!(1_u64),
+                StorageKey::from(2_u64) => stark_felt!(2_u64),
+            },
+        },
+        declared_classes: indexmap! { class_hash!(1_u64) => CompiledClassHash(stark_felt!(1_u64)) },
+        deprecated_declared_classes: vec![class_hash!(3_u64)],
+        nonces: indexmap! { contract_address!(2_u64) => Nonce(stark_felt!(1_u64)) },
+        replaced_classes: indexmap! { contract_address!(4_u64) => class_hash!(3_u64) },
+    };
+    let second = ThinStateDiff {
+        deployed_contracts: indexmap! { contract_address!(1_u64) => class_hash!(3_u64) },
+        storage_diffs: indexmap! {
+            contract_address!(2_u64) => indexmap! { StorageKey::from(2_u64) => stark_felt!(3_u64) },
+            contract_address!(1_u64) => indexmap! { StorageKey::from(1_u64) => stark_felt!(4_u64) },
+        },
+        declared_classes: indexmap! { class_hash!(2_u64) => CompiledClassHash(stark_felt!(2_u64)) },
+        deprecated_declared_classes: vec![class_hash!(3_u64)],
+        nonces: indexmap! { contract_address!(2_u64) => Nonce(stark_felt!(2_u64)) },
+        replaced_classes: indexmap! { contract_address!(4_u64) => class_hash!(2_u64) },
+    };
+    let third = ThinStateDiff {
+        // Replacing the class of a contract deployed in the range.
+        replaced_classes: indexmap! { contract_address!(2_u64) => class_hash!(2_u64) },
+        ..ThinStateDiff::default()
+    };
+
+    let expected = ThinStateDiff {
+        deployed_contracts: indexmap! {
+            contract_address!(1_u64) => class_hash!(3_u64),
+            contract_address!(2_u64) => class_hash!(2_u64),
+        },
+        storage_diffs: indexmap! {
+            contract_address!(1_u64) => indexmap! { StorageKey::from(1_u64) => stark_felt!(4_u64) },
+            contract_address!(2_u64) => indexmap! {
+                StorageKey::from(1_u64) => stark_felt!(1_u64),
+                StorageKey::from(2_u64) => stark_felt!(3_u64),
+            },
+        },
+        declared_classes: indexmap! {
+            class_hash!(1_u64) => CompiledClassHash(stark_felt!(1_u64)),
+            class_hash!(2_u64) => CompiledClassHash(stark_felt!(2_u64)),
+        },
+        deprecated_declared_classes: vec![class_hash!(3_u64)],
+        nonces: indexmap! { contract_address!(2_u64) => Nonce(stark_felt!(2_u64)) },
+        replaced_classes: indexmap! { contract_address!(4_u64) => class_hash!(2_u64) },
+    };
+    let squashed = ThinStateDiff::squash([first, second, third]);
+    assert_eq!(squashed, expected);
+    // IndexMap equality ignores the order, so check the invariants as well.
+    squashed.validate().unwrap();
+}
+
+#[test]
+fn state_diff_merge() {
+    let earlier = StateDiff {
+        deployed_contracts: indexmap! { contract_address!(2_u64) => class_hash!(1_u64) },
+        deprecated_declared_classes: indexmap! {
+            class_hash!(1_u64) => DeprecatedContractClass::default(),
+        },
+        ..StateDiff::default()
+    };
+    let later = StateDiff {
+        storage_diffs: indexmap! {
+            contract_address!(1_u64) => indexmap! { StorageKey::from(1_u64) => stark_felt!(1_u64) },
+        },
+        declared_classes: indexmap! {
+            class_hash!(2_u64) => (CompiledClassHash(stark_felt!(3_u64)), ContractClass::default()),
+        },
+        replaced_classes: indexmap! { contract_address!(2_u64) => class_hash!(2_u64) },
+        ..StateDiff::default()
+    };
+
+    let expected = StateDiff {
+        deployed_contracts: indexmap! { contract_address!(2_u64) => class_hash!(2_u64) },
+        storage_diffs: later.storage_diffs.clone(),
+        declared_classes: later.declared_classes.clone(),
+        deprecated_declared_classes: earlier.deprecated_declared_classes.clone(),
+        ..StateDiff::default()
+    };
+    let merged = earlier.merge(later);
+    assert_eq!(merged, expected);
+    merged.validate().unwrap();
+}
+
+fn get_reversible_state_diff() -> ReversibleStateDiff {
+    let diff = ThinStateDiff {
+        deployed_contracts: indexmap! { contract_address!(1_u64) => class_hash!(1_u64) },
+        storage_diffs: indexmap! {
+            contract_address!(1_u64) => indexmap! { StorageKey::from(1_u64) => stark_felt!(1_u64) },
+            contract_address!(2_u64) => indexmap! { StorageKey::from(2_u64) => stark_felt!(2_u64) },
+        },
+        nonces: indexmap! { contract_address!(2_u64) => Nonce(stark_felt!(3_u64)) },
+        replaced_classes: indexmap! { contract_address!(2_u64) => class_hash!(2_u64) },
+        ..ThinStateDiff::default()
+    };
+    ReversibleStateDiff::new(
+        diff,
+        indexmap! {
+            contract_address!(2_u64) => indexmap! { StorageKey::from(2_u64) => stark_felt!(5_u64) },
+            contract_address!(1_u64) => indexmap! { StorageKey::from(1_u64) => StarkFelt::ZERO },
+        },
+        indexmap! { contract_address!(2_u64) => Nonce(stark_felt!(2_u64)) },
+        indexmap! {
+            contract_address!(2_u64) => class_hash!(3_u64),
+            contract_address!(1_u64) => ClassHash::default(),
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+fn reversible_state_diff_invert() {
+    let reversible_diff = get_reversible_state_diff();
+    let inverse = reversible_diff.invert();
+
+    let expected_diff = ThinStateDiff {
+        storage_diffs: indexmap! {
+            contract_address!(1_u64) => indexmap! { StorageKey::from(1_u64) => StarkFelt::ZERO },
+            contract_address!(2_u64) => indexmap! { StorageKey::from(2_u64) => stark_felt!(5_u64) },
+        },
+        nonces: indexmap! { contract_address!(2_u64) => Nonce(stark_felt!(2_u64)) },
+        replaced_classes: indexmap! {
+            contract_address!(1_u64) => ClassHash::default(),
+            contract_address!(2_u64) => class_hash!(3_u64),
+        },
+        ..ThinStateDiff::default()
+    };
+    assert_eq!(inverse.diff, expected_diff);
+    inverse.diff.validate().unwrap();
+
+    // Undoing the undo redoes the diff, with the deployment as a class replacement.
+    let mut expected_redo = reversible_diff.clone();
+    let deployed_contracts = std::mem::take(&mut expected_redo.diff.deployed_contracts);
+    expected_redo.diff.replaced_classes.extend(deployed_contracts);
+    expected_redo.diff.normalize();
+    assert_eq!(inverse.invert(), expected_redo);
+}
+
+#[test]
+fn reversible_state_diff_previous_values_mismatch() {
+    let reversible_diff = get_reversible_state_diff();
+    let mut previous_nonces = reversible_diff.previous_nonces.clone();
+    previous_nonces.insert(contract_address!(3_u64), Nonce::default());
+    let result = ReversibleStateDiff::new(
+        reversible_diff.diff,
+        reversible_diff.previous_storage_values,
+        previous_nonces,
+        reversible_diff.previous_class_hashes,
+    );
+    assert_matches!(
+        result,
+        Err(StarknetApiError::StateDiff(StateDiffError::PreviousValuesMismatch("nonces")))
+    );
+}
+
+fn get_da_state_diff() -> ThinStateDiff {
+    ThinStateDiff {
+        deployed_contracts: indexmap! { contract_address!(1_u64) => class_hash!("0x10") },
+        storage_diffs: indexmap! {
+            contract_address!(1_u64) => indexmap! {
+                StorageKey::from(2_u64) => stark_felt!(5_u64),
+                StorageKey::from(1_u64) => stark_felt!(4_u64),
+            },
+            contract_address!(4_u64) => indexmap! {},
+        },
+        declared_classes: indexmap! { class_hash!("0x40") => CompiledClassHash(stark_felt!("0x41")) },
+        deprecated_declared_classes: vec![class_hash!("0x50")],
+        nonces: indexmap! {
+            contract_address!(2_u64) => Nonce(stark_felt!(1_u64)),
+            contract_address!(1_u64) => Nonce(stark_felt!(3_u64)),
+        },
+        replaced_classes: indexmap! { contract_address!(3_u64) => class_hash!("0x30") },
+    }
+}
+
+// The state before the diff of get_da_state_diff.
+fn get_da_state() -> InMemoryState {
+    let mut state = InMemoryState::new();
+    state.apply(&StateDiff {
+        nonces: indexmap! { contract_address!(3_u64) => Nonce(stark_felt!(7_u64)) },
+        ..StateDiff::default()
+    });
+    state
+}
+
+#[test]
+fn thin_state_diff_encode_da() {
+    let calldata =
+        get_da_state_diff().encode_da(&get_da_state(), L1DataAvailabilityMode::Calldata).unwrap();
+    let expected_felts = vec![
+        stark_felt!(3_u8),
+        // Deployed, with nonce 3 and 2 storage updates.
+        stark_felt!(1_u8),
+        stark_felt!("0x100000000000000030000000000000002"),
+        stark_felt!("0x10"),
+        stark_felt!(1_u8),
+        stark_felt!(4_u8),
+        stark_felt!(2_u8),
+        stark_felt!(5_u8),
+        // Only the nonce changes.
+        stark_felt!(2_u8),
+        stark_felt!("0x10000000000000000"),
+        // Only the class is replaced, and the nonce is the current one.
+        stark_felt!(3_u8),
+        stark_felt!("0x100000000000000070000000000000000"),
+        stark_felt!("0x30"),
+        stark_felt!(1_u8),
+        stark_felt!("0x40"),
+        stark_felt!("0x41"),
+    ];
+    assert_eq!(calldata, felts_to_calldata(&expected_felts));
+}
+
+#[test]
+fn thin_state_diff_decode_da() {
+    let expected = ThinStateDiff {
+        deployed_contracts: indexmap! {
+            contract_address!(1_u64) => class_hash!("0x10"),
+            contract_address!(3_u64) => class_hash!("0x30"),
+        },
+        storage_diffs: indexmap! {
+            contract_address!(1_u64) => indexmap! {
+                StorageKey::from(1_u64) => stark_felt!(4_u64),
+                StorageKey::from(2_u64) => stark_felt!(5_u64),
+            },
+        },
+        declared_classes: indexmap! { class_hash!("0x40") => CompiledClassHash(stark_felt!("0x41")) },
+        nonces: indexmap! {
+            contract_address!(1_u64) => Nonce(stark_felt!(3_u64)),
+            contract_address!(2_u64) => Nonce(stark_felt!(1_u64)),
+        },
+        ..ThinStateDiff::default()
+    };
+    let state = get_da_state();
+    for mode in [L1DataAvailabilityMode::Calldata, L1DataAvailabilityMode::Blob] {
+        let data = get_da_state_diff().encode_da(&state, mode).unwrap();
+        assert_eq!(ThinStateDiff::decode_da(&data, &state, mode).unwrap(), expected);
+    }
+
+    // Against another state, the nonce that did not change is returned as changed.
+    let mode = L1DataAvailabilityMode::Calldata;
+    let data = get_da_state_diff().encode_da(&state, mode).unwrap();
+    let mut expected_nonces = expected.nonces.clone();
+    expected_nonces.insert(contract_address!(3_u64), Nonce(stark_felt!(7_u64)));
+    assert_eq!(
+        ThinStateDiff::decode_da(&data, &InMemoryState::new(), mode).unwrap().nonces,
+        expected_nonces
+    );
+}
+
+#[test]
+fn thin_state_diff_decode_da_invalid_data() {
+    let mode = L1DataAvailabilityMode::Calldata;
+    let state = get_da_state();
+    let calldata = get_da_state_diff().encode_da(&state, mode).unwrap();
+
+    let truncated = &calldata[..calldata.len() - BYTES_PER_FIELD_ELEMENT];
+    assert_matches!(
+        ThinStateDiff::decode_da(truncated, &state, mode),
+        Err(StarknetApiError::InvalidDataAvailabilityEncoding(_))
+    );
+
+    let mut trailing_data = calldata.clone();
+    trailing_data.extend(felts_to_calldata(&[StarkFelt::ZERO, StarkFelt::ONE]));
+    assert_matches!(
+        ThinStateDiff::decode_da(&trailing_data, &state, mode),
+        Err(StarknetApiError::InvalidDataAvailabilityEncoding(_))
+    );
+
+    let invalid_word = felts_to_calldata(&[
+        StarkFelt::ONE,
+        StarkFelt::ONE,
+        stark_felt!("0x200000000000000000000000000000000"),
+    ]);
+    assert_matches!(
+        ThinStateDiff::decode_da(&invalid_word, &state, mode),
+        Err(StarknetApiError::InvalidDataAvailabilityEncoding(_))
+    );
+}
+
+#[test]
+fn contract_class_hash() {
+    let class = get_minimal_contract_class();
+    // The hash of the class by a separate implementation of the Sierra class hash.
+    assert_eq!(
+        class.class_hash().unwrap(),
+        class_hash!("0xdd294770e647d4c34f1953c5b5f19e9644a8413bfbc46f5981d9b99ceb6e32")
+    );
+    assert_eq!(class.class_hash_with_hasher::<Poseidon>().unwrap(), class.class_hash().unwrap());
+    assert_ne!(class.class_hash_with_hasher::<Pedersen>().unwrap(), class.class_hash().unwrap());
+}
+
+#[test]
+fn contract_class_hash_unsupported_version() {
+    for version in ["", "0.2.0"] {
+        let class = ContractClass {
+            contract_class_version: version.to_owned(),
+            ..ContractClass::default()
+        };
+        assert_matches!(
+            class.class_hash(),
+            Err(StarknetApiError::UnsupportedContractClassVersion(unsupported))
+            if unsupported == version
+        );
+    }
+}
+
+#[test]
+fn contract_class_without_version() {
+    // Classes serialized before the version was added to them.
+    let class: ContractClass =
+        serde_json::from_value(json!({"sierra_program": [], "entry_point_by_type": {}, "abi": ""}))
+            .unwrap();
+    assert_eq!(class.contract_class_version, CONTRACT_CLASS_VERSION);
+}
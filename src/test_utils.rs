@@ -0,0 +1,13 @@
+use crate::compiled_class::{AllowedLibfuncs, CompileOptions};
+use crate::state::ContractClass;
+
+/// Options that compile any supported class: no limit on the bytecode size and the default list
+/// of allowed libfuncs.
+pub(crate) fn get_compile_options() -> CompileOptions {
+    CompileOptions { max_bytecode_size: usize::MAX, allowed_libfuncs: AllowedLibfuncs::Default }
+}
+
+/// The minimal contract of the test data of the Cairo compiler.
+pub(crate) fn get_minimal_contract_class() -> ContractClass {
+    serde_json::from_str(include_str!("../resources/minimal_contract_class.json")).unwrap()
+}
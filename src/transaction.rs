@@ -1,15 +1,25 @@
+#[cfg(test)]
+#[path = "transaction_test.rs"]
+mod transaction_test;
+
+use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::sync::Arc;
 
 use derive_more::From;
-use serde::{Deserialize, Serialize};
+use serde::de::Error as DeserializationError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::StarknetApiError;
 use crate::block::{BlockHash, BlockNumber};
+use crate::compiled_class::CompileOptions;
 use crate::core::{
     ClassHash, CompiledClassHash, ContractAddress, EntryPointSelector, EthAddress, Nonce,
 };
-use crate::hash::{StarkFelt, StarkHash};
-use crate::serde_utils::PrefixedBytesAsHex;
+use crate::data_availability::DataAvailabilityMode;
+use crate::hash::{StarkFelt, StarkHash, starknet_keccak};
+use crate::serde_utils::{BytesAsHex, PrefixedBytesAsHex};
+use crate::state::ContractClass;
 
 /// A transaction.
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
@@ -26,6 +36,18 @@ pub enum Transaction {
     L1Handler(L1HandlerTransaction),
 }
 
+impl Transaction {
+    /// Returns the signature of the transaction; deploy and L1 handler transactions are unsigned.
+    pub fn signature(&self) -> TransactionSignature {
+        match self {
+            Transaction::Declare(tx) => tx.signature(),
+            Transaction::DeployAccount(tx) => tx.signature(),
+            Transaction::Invoke(tx) => tx.signature(),
+            Transaction::Deploy(_) | Transaction::L1Handler(_) => TransactionSignature::default(),
+        }
+    }
+}
+
 /// A transaction output.
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
 pub enum TransactionOutput {
@@ -61,6 +83,36 @@ impl TransactionOutput {
             TransactionOutput::L1Handler(output) => &output.events,
         }
     }
+
+    pub fn messages_sent(&self) -> &[MessageToL1] {
+        match self {
+            TransactionOutput::Declare(output) => &output.messages_sent,
+            TransactionOutput::Deploy(output) => &output.messages_sent,
+            TransactionOutput::DeployAccount(output) => &output.messages_sent,
+            TransactionOutput::Invoke(output) => &output.messages_sent,
+            TransactionOutput::L1Handler(output) => &output.messages_sent,
+        }
+    }
+
+    pub fn execution_status(&self) -> &TransactionExecutionStatus {
+        match self {
+            TransactionOutput::Declare(output) => &output.execution_status,
+            TransactionOutput::Deploy(output) => &output.execution_status,
+            TransactionOutput::DeployAccount(output) => &output.execution_status,
+            TransactionOutput::Invoke(output) => &output.execution_status,
+            TransactionOutput::L1Handler(output) => &output.execution_status,
+        }
+    }
+
+    pub fn gas_consumed(&self) -> GasVector {
+        match self {
+            TransactionOutput::Declare(output) => output.gas_consumed,
+            TransactionOutput::Deploy(output) => output.gas_consumed,
+            TransactionOutput::DeployAccount(output) => output.gas_consumed,
+            TransactionOutput::Invoke(output) => output.gas_consumed,
+            TransactionOutput::L1Handler(output) => output.gas_consumed,
+        }
+    }
 }
 
 /// A declare V0 or V1 transaction (same schema but different version).
@@ -84,11 +136,59 @@ pub struct DeclareTransactionV2 {
     pub sender_address: ContractAddress,
 }
 
+impl DeclareTransactionV2 {
+    /// Verifies that the transaction declares the given class: that its class hash is the hash of
+    /// the class, and that its compiled class hash is the hash of the class compiled with the
+    /// given options.
+    ///
+    /// The class is compiled by [`ContractClass::compile`], so only classes of the Sierra versions
+    /// that it supports can be verified, and only against the compiled class hashes of its
+    /// compiler version.
+    pub fn verify_class(
+        &self,
+        contract_class: &ContractClass,
+        options: CompileOptions,
+    ) -> Result<(), StarknetApiError> {
+        let class_hash = contract_class.class_hash()?;
+        if class_hash != self.class_hash {
+            return Err(StarknetApiError::ClassHashMismatch {
+                expected: self.class_hash,
+                calculated: class_hash,
+            });
+        }
+        let compiled_class_hash = contract_class.compile(options)?.compiled_class_hash()?;
+        if compiled_class_hash != self.compiled_class_hash {
+            return Err(StarknetApiError::CompiledClassHashMismatch {
+                expected: self.compiled_class_hash,
+                calculated: compiled_class_hash,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A declare V3 transaction.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct DeclareTransactionV3 {
+    pub resource_bounds: ResourceBoundsMapping,
+    pub tip: Tip,
+    pub signature: TransactionSignature,
+    pub nonce: Nonce,
+    pub class_hash: ClassHash,
+    pub compiled_class_hash: CompiledClassHash,
+    pub sender_address: ContractAddress,
+    pub nonce_data_availability_mode: DataAvailabilityMode,
+    pub fee_data_availability_mode: DataAvailabilityMode,
+    pub paymaster_data: PaymasterData,
+    pub account_deployment_data: AccountDeploymentData,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
 pub enum DeclareTransaction {
     V0(DeclareTransactionV0V1),
     V1(DeclareTransactionV0V1),
     V2(DeclareTransactionV2),
+    V3(DeclareTransactionV3),
 }
 
 macro_rules! implement_declare_tx_getters {
@@ -98,6 +198,7 @@ macro_rules! implement_declare_tx_getters {
                 Self::V0(tx) => tx.$field.clone(),
                 Self::V1(tx) => tx.$field.clone(),
                 Self::V2(tx) => tx.$field.clone(),
+                Self::V3(tx) => tx.$field.clone(),
             }
         })*
     };
@@ -108,24 +209,34 @@ impl DeclareTransaction {
         (class_hash, ClassHash),
         (nonce, Nonce),
         (sender_address, ContractAddress),
-        (max_fee, Fee),
         (signature, TransactionSignature)
     );
 
+    /// The maximal fee of the transaction, which for a V3 transaction is the maximal fee of its
+    /// resource bounds.
+    pub fn max_fee(&self) -> Fee {
+        match self {
+            DeclareTransaction::V0(tx) => tx.max_fee,
+            DeclareTransaction::V1(tx) => tx.max_fee,
+            DeclareTransaction::V2(tx) => tx.max_fee,
+            DeclareTransaction::V3(tx) => tx.resource_bounds.max_fee(),
+        }
+    }
+
     pub fn version(&self) -> TransactionVersion {
         match self {
             DeclareTransaction::V0(_) => TransactionVersion(StarkFelt::from(0_u8)),
             DeclareTransaction::V1(_) => TransactionVersion(StarkFelt::from(1_u8)),
             DeclareTransaction::V2(_) => TransactionVersion(StarkFelt::from(2_u8)),
+            DeclareTransaction::V3(_) => TransactionVersion(StarkFelt::from(3_u8)),
         }
     }
 }
 
-/// A deploy account transaction.
+/// A deploy account V1 transaction.
 #[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
-pub struct DeployAccountTransaction {
+pub struct DeployAccountTransactionV1 {
     pub max_fee: Fee,
-    pub version: TransactionVersion,
     pub signature: TransactionSignature,
     pub nonce: Nonce,
     pub class_hash: ClassHash,
@@ -133,6 +244,112 @@ pub struct DeployAccountTransaction {
     pub constructor_calldata: Calldata,
 }
 
+/// A deploy account V3 transaction.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct DeployAccountTransactionV3 {
+    pub resource_bounds: ResourceBoundsMapping,
+    pub tip: Tip,
+    pub signature: TransactionSignature,
+    pub nonce: Nonce,
+    pub class_hash: ClassHash,
+    pub contract_address_salt: ContractAddressSalt,
+    pub constructor_calldata: Calldata,
+    pub nonce_data_availability_mode: DataAvailabilityMode,
+    pub fee_data_availability_mode: DataAvailabilityMode,
+    pub paymaster_data: PaymasterData,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, PartialOrd, Ord, From)]
+pub enum DeployAccountTransaction {
+    V1(DeployAccountTransactionV1),
+    V3(DeployAccountTransactionV3),
+}
+
+impl<'de> Deserialize<'de> for DeployAccountTransaction {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        enum Versioned {
+            V1(DeployAccountTransactionV1),
+            V3(DeployAccountTransactionV3),
+        }
+        // Deploy account transactions stored before V3 are a single struct with a version.
+        #[derive(Deserialize)]
+        struct Unversioned {
+            max_fee: Fee,
+            version: TransactionVersion,
+            signature: TransactionSignature,
+            nonce: Nonce,
+            class_hash: ClassHash,
+            contract_address_salt: ContractAddressSalt,
+            constructor_calldata: Calldata,
+        }
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum StoredTransaction {
+            Versioned(Versioned),
+            Unversioned(Unversioned),
+        }
+
+        Ok(match StoredTransaction::deserialize(deserializer)? {
+            StoredTransaction::Versioned(Versioned::V1(tx)) => Self::V1(tx),
+            StoredTransaction::Versioned(Versioned::V3(tx)) => Self::V3(tx),
+            StoredTransaction::Unversioned(tx) => {
+                if tx.version != TransactionVersion(StarkFelt::from(1_u8)) {
+                    return Err(DeserializationError::custom(format!(
+                        "Unsupported deploy account transaction version {}.",
+                        tx.version.0
+                    )));
+                }
+                Self::V1(DeployAccountTransactionV1 {
+                    max_fee: tx.max_fee,
+                    signature: tx.signature,
+                    nonce: tx.nonce,
+                    class_hash: tx.class_hash,
+                    contract_address_salt: tx.contract_address_salt,
+                    constructor_calldata: tx.constructor_calldata,
+                })
+            }
+        })
+    }
+}
+
+macro_rules! implement_deploy_account_tx_getters {
+    ($(($field:ident, $field_type:ty)),*) => {
+        $(pub fn $field(&self) -> $field_type {
+            match self {
+                Self::V1(tx) => tx.$field.clone(),
+                Self::V3(tx) => tx.$field.clone(),
+            }
+        })*
+    };
+}
+
+impl DeployAccountTransaction {
+    implement_deploy_account_tx_getters!(
+        (class_hash, ClassHash),
+        (constructor_calldata, Calldata),
+        (contract_address_salt, ContractAddressSalt),
+        (nonce, Nonce),
+        (signature, TransactionSignature)
+    );
+
+    /// The maximal fee of the transaction, which for a V3 transaction is the maximal fee of its
+    /// resource bounds.
+    pub fn max_fee(&self) -> Fee {
+        match self {
+            DeployAccountTransaction::V1(tx) => tx.max_fee,
+            DeployAccountTransaction::V3(tx) => tx.resource_bounds.max_fee(),
+        }
+    }
+
+    pub fn version(&self) -> TransactionVersion {
+        match self {
+            DeployAccountTransaction::V1(_) => TransactionVersion(StarkFelt::from(1_u8)),
+            DeployAccountTransaction::V3(_) => TransactionVersion(StarkFelt::from(3_u8)),
+        }
+    }
+}
+
 /// A deploy transaction.
 #[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
 pub struct DeployTransaction {
@@ -162,10 +379,26 @@ pub struct InvokeTransactionV1 {
     pub calldata: Calldata,
 }
 
+/// An invoke V3 transaction.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct InvokeTransactionV3 {
+    pub resource_bounds: ResourceBoundsMapping,
+    pub tip: Tip,
+    pub signature: TransactionSignature,
+    pub nonce: Nonce,
+    pub sender_address: ContractAddress,
+    pub calldata: Calldata,
+    pub nonce_data_availability_mode: DataAvailabilityMode,
+    pub fee_data_availability_mode: DataAvailabilityMode,
+    pub paymaster_data: PaymasterData,
+    pub account_deployment_data: AccountDeploymentData,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord, From)]
 pub enum InvokeTransaction {
     V0(InvokeTransactionV0),
     V1(InvokeTransactionV1),
+    V3(InvokeTransactionV3),
 }
 
 macro_rules! implement_invoke_tx_getters {
@@ -174,17 +407,32 @@ macro_rules! implement_invoke_tx_getters {
             match self {
                 Self::V0(tx) => tx.$field.clone(),
                 Self::V1(tx) => tx.$field.clone(),
+                Self::V3(tx) => tx.$field.clone(),
             }
         })*
     };
 }
 
 impl InvokeTransaction {
-    implement_invoke_tx_getters!(
-        (max_fee, Fee),
-        (signature, TransactionSignature),
-        (calldata, Calldata)
-    );
+    implement_invoke_tx_getters!((signature, TransactionSignature), (calldata, Calldata));
+
+    /// The maximal fee of the transaction, which for a V3 transaction is the maximal fee of its
+    /// resource bounds.
+    pub fn max_fee(&self) -> Fee {
+        match self {
+            InvokeTransaction::V0(tx) => tx.max_fee,
+            InvokeTransaction::V1(tx) => tx.max_fee,
+            InvokeTransaction::V3(tx) => tx.resource_bounds.max_fee(),
+        }
+    }
+
+    pub fn version(&self) -> TransactionVersion {
+        match self {
+            InvokeTransaction::V0(_) => TransactionVersion(StarkFelt::from(0_u8)),
+            InvokeTransaction::V1(_) => TransactionVersion(StarkFelt::from(1_u8)),
+            InvokeTransaction::V3(_) => TransactionVersion(StarkFelt::from(3_u8)),
+        }
+    }
 }
 
 /// An L1 handler transaction.
@@ -204,6 +452,9 @@ pub struct DeclareTransactionOutput {
     pub messages_sent: Vec<MessageToL1>,
     pub events: Vec<Event>,
     pub execution_status: TransactionExecutionStatus,
+    // Not reported by old versions of Starknet.
+    #[serde(default)]
+    pub gas_consumed: GasVector,
 }
 
 /// A deploy-account transaction output.
@@ -214,6 +465,9 @@ pub struct DeployAccountTransactionOutput {
     pub events: Vec<Event>,
     pub contract_address: ContractAddress,
     pub execution_status: TransactionExecutionStatus,
+    // Not reported by old versions of Starknet.
+    #[serde(default)]
+    pub gas_consumed: GasVector,
 }
 
 /// A deploy transaction output.
@@ -224,6 +478,9 @@ pub struct DeployTransactionOutput {
     pub events: Vec<Event>,
     pub contract_address: ContractAddress,
     pub execution_status: TransactionExecutionStatus,
+    // Not reported by old versions of Starknet.
+    #[serde(default)]
+    pub gas_consumed: GasVector,
 }
 
 /// An invoke transaction output.
@@ -233,6 +490,9 @@ pub struct InvokeTransactionOutput {
     pub messages_sent: Vec<MessageToL1>,
     pub events: Vec<Event>,
     pub execution_status: TransactionExecutionStatus,
+    // Not reported by old versions of Starknet.
+    #[serde(default)]
+    pub gas_consumed: GasVector,
 }
 
 /// An L1 handler transaction output.
@@ -242,6 +502,9 @@ pub struct L1HandlerTransactionOutput {
     pub messages_sent: Vec<MessageToL1>,
     pub events: Vec<Event>,
     pub execution_status: TransactionExecutionStatus,
+    // Not reported by old versions of Starknet.
+    #[serde(default)]
+    pub gas_consumed: GasVector,
 }
 
 /// A transaction receipt.
@@ -255,15 +518,86 @@ pub struct TransactionReceipt {
 }
 
 /// Transaction execution status.
-#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord, Default)]
+///
+/// A reverted status without a revert reason is serialized as a plain "REVERTED", as before the
+/// revert reason was added.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, PartialOrd, Ord, Default)]
 pub enum TransactionExecutionStatus {
-    #[serde(rename = "SUCCEEDED")]
     #[default]
     // Succeeded is the default variant because old versions of Starknet don't have an execution
     // status and every transaction is considered succeeded
     Succeeded,
-    #[serde(rename = "REVERTED")]
-    Reverted,
+    Reverted(RevertedTransactionExecutionStatus),
+}
+
+impl<'de> Deserialize<'de> for TransactionExecutionStatus {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        enum Status {
+            #[serde(rename = "SUCCEEDED")]
+            Succeeded,
+            #[serde(rename = "REVERTED")]
+            Reverted(RevertedTransactionExecutionStatus),
+        }
+        // Statuses stored before the revert reason was added are a plain "REVERTED".
+        #[derive(Deserialize)]
+        enum StatusWithoutRevertReason {
+            #[serde(rename = "REVERTED")]
+            Reverted,
+        }
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum StoredStatus {
+            Status(Status),
+            WithoutRevertReason(StatusWithoutRevertReason),
+        }
+
+        Ok(match StoredStatus::deserialize(deserializer)? {
+            StoredStatus::Status(Status::Succeeded) => Self::Succeeded,
+            StoredStatus::Status(Status::Reverted(status)) => Self::Reverted(status),
+            StoredStatus::WithoutRevertReason(StatusWithoutRevertReason::Reverted) => {
+                Self::Reverted(RevertedTransactionExecutionStatus::default())
+            }
+        })
+    }
+}
+
+impl Serialize for TransactionExecutionStatus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        enum Status<'a> {
+            #[serde(rename = "SUCCEEDED")]
+            Succeeded,
+            #[serde(rename = "REVERTED")]
+            Reverted(&'a RevertedTransactionExecutionStatus),
+            #[serde(rename = "REVERTED")]
+            RevertedWithoutRevertReason,
+        }
+
+        match self {
+            Self::Succeeded => Status::Succeeded,
+            Self::Reverted(status) if status.revert_reason.is_empty() => {
+                Status::RevertedWithoutRevertReason
+            }
+            Self::Reverted(status) => Status::Reverted(status),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// The status of a reverted transaction.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct RevertedTransactionExecutionStatus {
+    pub revert_reason: String,
+}
+
+/// The gas consumed by a transaction, per resource.
+#[derive(
+    Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord,
+)]
+pub struct GasVector {
+    pub l1_gas: u64,
+    pub l1_data_gas: u64,
 }
 
 /// A fee.
@@ -291,6 +625,76 @@ impl From<Fee> for StarkFelt {
     }
 }
 
+/// A tip paid to the sequencer, on top of the fee, to prioritize a V3 transaction.
+#[derive(
+    Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord,
+)]
+#[serde(from = "PrefixedBytesAsHex<8_usize>", into = "PrefixedBytesAsHex<8_usize>")]
+pub struct Tip(pub u64);
+
+impl From<PrefixedBytesAsHex<8_usize>> for Tip {
+    fn from(val: PrefixedBytesAsHex<8_usize>) -> Self {
+        Self(u64::from_be_bytes(val.0))
+    }
+}
+
+impl From<Tip> for PrefixedBytesAsHex<8_usize> {
+    fn from(tip: Tip) -> Self {
+        Self(tip.0.to_be_bytes())
+    }
+}
+
+impl From<Tip> for StarkFelt {
+    fn from(tip: Tip) -> Self {
+        Self::from(tip.0)
+    }
+}
+
+/// A resource whose usage is bounded by a V3 transaction.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub enum Resource {
+    #[serde(rename = "L1_GAS")]
+    L1Gas,
+    #[serde(rename = "L2_GAS")]
+    L2Gas,
+}
+
+/// The maximal amount of a [Resource](`crate::transaction::Resource`) a transaction may use, and
+/// the maximal price it is willing to pay per unit.
+#[derive(
+    Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord,
+)]
+pub struct ResourceBounds {
+    #[serde(serialize_with = "u64_to_hex", deserialize_with = "u64_from_hex")]
+    pub max_amount: u64,
+    #[serde(serialize_with = "u128_to_hex", deserialize_with = "u128_from_hex")]
+    pub max_price_per_unit: u128,
+}
+
+/// The [ResourceBounds](`crate::transaction::ResourceBounds`) of each resource.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct ResourceBoundsMapping(pub BTreeMap<Resource, ResourceBounds>);
+
+impl ResourceBoundsMapping {
+    /// The fee paid if every resource is used up to its maximal amount at its maximal price,
+    /// saturating at the maximal [Fee](`crate::transaction::Fee`).
+    pub fn max_fee(&self) -> Fee {
+        Fee(self.0.values().fold(0_u128, |max_fee, bounds| {
+            max_fee.saturating_add(
+                u128::from(bounds.max_amount).saturating_mul(bounds.max_price_per_unit),
+            )
+        }))
+    }
+}
+
+/// The data of a paymaster that pays for a V3 transaction.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct PaymasterData(pub Vec<StarkFelt>);
+
+/// The data used to deploy the account sending a V3 transaction.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct AccountDeploymentData(pub Vec<StarkFelt>);
+
 /// The hash of a [Transaction](`crate::transaction::Transaction`).
 #[derive(
     Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord,
@@ -372,6 +776,13 @@ pub struct EventContent {
 #[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
 pub struct EventKey(pub StarkFelt);
 
+impl EventKey {
+    /// Returns the key identifying an event with the given name.
+    pub fn from_name(name: &str) -> Self {
+        Self(starknet_keccak(name.as_bytes()))
+    }
+}
+
 /// An event data.
 #[derive(Debug, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
 pub struct EventData(pub Vec<StarkFelt>);
@@ -387,3 +798,19 @@ pub struct TransactionOffsetInBlock(pub usize);
     Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord,
 )]
 pub struct EventIndexInTransactionOutput(pub usize);
+
+fn u64_to_hex<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    PrefixedBytesAsHex::<8_usize>::serialize(&BytesAsHex(value.to_be_bytes()), serializer)
+}
+
+fn u64_from_hex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    Ok(u64::from_be_bytes(PrefixedBytesAsHex::<8_usize>::deserialize(deserializer)?.0))
+}
+
+fn u128_to_hex<S: Serializer>(value: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+    PrefixedBytesAsHex::<16_usize>::serialize(&BytesAsHex(value.to_be_bytes()), serializer)
+}
+
+fn u128_from_hex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+    Ok(u128::from_be_bytes(PrefixedBytesAsHex::<16_usize>::deserialize(deserializer)?.0))
+}
@@ -0,0 +1,460 @@
+//! Calculation of [transaction hashes](`crate::transaction::TransactionHash`), as defined
+//! in <https://docs.starknet.io/documentation/architecture_and_concepts/Network_Architecture/transactions/>.
+#[cfg(test)]
+#[path = "transaction_hash_test.rs"]
+mod transaction_hash_test;
+
+use once_cell::sync::Lazy;
+
+use crate::StarknetApiError;
+use crate::block::BlockNumber;
+use crate::core::{
+    CONSTRUCTOR_ENTRY_POINT_SELECTOR, ChainId, ContractAddress, calculate_contract_address,
+};
+use crate::data_availability::DataAvailabilityMode;
+use crate::hash::{HashChain, StarkFelt, ascii_as_felt, lazy_ascii_as_felt};
+use crate::transaction::{
+    DeclareTransaction, DeclareTransactionV0V1, DeclareTransactionV2, DeclareTransactionV3,
+    DeployAccountTransaction, DeployAccountTransactionV1, DeployAccountTransactionV3,
+    DeployTransaction, InvokeTransaction, InvokeTransactionV0, InvokeTransactionV1,
+    InvokeTransactionV3, L1HandlerTransaction, Resource, ResourceBounds, ResourceBoundsMapping,
+    Tip, Transaction, TransactionHash, TransactionVersion,
+};
+
+static DECLARE: Lazy<StarkFelt> = lazy_ascii_as_felt!("declare");
+static DEPLOY: Lazy<StarkFelt> = lazy_ascii_as_felt!("deploy");
+static DEPLOY_ACCOUNT: Lazy<StarkFelt> = lazy_ascii_as_felt!("deploy_account");
+static INVOKE: Lazy<StarkFelt> = lazy_ascii_as_felt!("invoke");
+static L1_HANDLER: Lazy<StarkFelt> = lazy_ascii_as_felt!("l1_handler");
+
+// The names of the resources, as encoded in the hash of V3 transactions.
+const L1_GAS: &[u8; 7] = b"\0L1_GAS";
+const L2_GAS: &[u8; 7] = b"\0L2_GAS";
+// The number of bits used to encode each data availability mode in V3 transactions.
+const DATA_AVAILABILITY_MODE_BITS: usize = 32;
+
+/// On mainnet, from this block number onwards there are no transactions with a deprecated hash.
+pub const MAINNET_TRANSACTION_HASH_WITH_VERSION: BlockNumber = BlockNumber(1470);
+
+// The historical hash schemes of L1 handler transactions.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum L1HandlerVersion {
+    // Hashed as an invoke transaction, without a version, a fee and a nonce.
+    AsInvoke,
+    // Hashed without a version, a fee and a nonce.
+    V0Deprecated,
+    V0,
+}
+
+/// Calculates the hash of a transaction.
+pub fn calculate_transaction_hash(
+    transaction: &Transaction,
+    chain_id: &ChainId,
+) -> Result<TransactionHash, StarknetApiError> {
+    match transaction {
+        Transaction::Declare(declare) => match declare {
+            DeclareTransaction::V0(declare_v0) => {
+                calculate_declare_v0_hash(declare_v0, chain_id, &declare.version())
+            }
+            DeclareTransaction::V1(declare_v1) => {
+                calculate_declare_v1_hash(declare_v1, chain_id, &declare.version())
+            }
+            DeclareTransaction::V2(declare_v2) => {
+                calculate_declare_v2_hash(declare_v2, chain_id, &declare.version())
+            }
+            DeclareTransaction::V3(declare_v3) => {
+                calculate_declare_v3_hash(declare_v3, chain_id, &declare.version())
+            }
+        },
+        Transaction::Deploy(deploy) => calculate_common_deploy_hash(deploy, chain_id, false),
+        Transaction::DeployAccount(deploy_account) => match deploy_account {
+            DeployAccountTransaction::V1(deploy_account_v1) => {
+                calculate_deploy_account_v1_hash(deploy_account_v1, chain_id)
+            }
+            DeployAccountTransaction::V3(deploy_account_v3) => {
+                calculate_deploy_account_v3_hash(deploy_account_v3, chain_id)
+            }
+        },
+        Transaction::Invoke(invoke) => match invoke {
+            InvokeTransaction::V0(invoke_v0) => {
+                calculate_common_invoke_v0_hash(invoke_v0, chain_id, false)
+            }
+            InvokeTransaction::V1(invoke_v1) => calculate_invoke_v1_hash(invoke_v1, chain_id),
+            InvokeTransaction::V3(invoke_v3) => calculate_invoke_v3_hash(invoke_v3, chain_id),
+        },
+        Transaction::L1Handler(l1_handler) => {
+            calculate_common_l1_handler_hash(l1_handler, chain_id, L1HandlerVersion::V0)
+        }
+    }
+}
+
+/// Calculates the hashes that older versions of Starknet may have given to a transaction, instead
+/// of the one returned by [`calculate_transaction_hash`].
+pub fn calculate_deprecated_transaction_hashes(
+    transaction: &Transaction,
+    chain_id: &ChainId,
+) -> Result<Vec<TransactionHash>, StarknetApiError> {
+    Ok(match transaction {
+        Transaction::Declare(_) | Transaction::DeployAccount(_) => vec![],
+        Transaction::Deploy(deploy) => vec![calculate_common_deploy_hash(deploy, chain_id, true)?],
+        Transaction::Invoke(InvokeTransaction::V0(invoke_v0)) => {
+            vec![calculate_common_invoke_v0_hash(invoke_v0, chain_id, true)?]
+        }
+        Transaction::Invoke(InvokeTransaction::V1(_) | InvokeTransaction::V3(_)) => vec![],
+        Transaction::L1Handler(l1_handler) => vec![
+            calculate_common_l1_handler_hash(l1_handler, chain_id, L1HandlerVersion::AsInvoke)?,
+            calculate_common_l1_handler_hash(l1_handler, chain_id, L1HandlerVersion::V0Deprecated)?,
+        ],
+    })
+}
+
+/// Returns whether `expected_hash` is a valid hash of a transaction in the given block, taking the
+/// deprecated hash schemes into account.
+pub fn validate_transaction_hash(
+    transaction: &Transaction,
+    block_number: &BlockNumber,
+    chain_id: &ChainId,
+    expected_hash: TransactionHash,
+) -> Result<bool, StarknetApiError> {
+    if calculate_transaction_hash(transaction, chain_id)? == expected_hash {
+        return Ok(true);
+    }
+    if chain_id == &ChainId("SN_MAIN".to_string())
+        && block_number > &MAINNET_TRANSACTION_HASH_WITH_VERSION
+    {
+        return Ok(false);
+    }
+    Ok(calculate_deprecated_transaction_hashes(transaction, chain_id)?.contains(&expected_hash))
+}
+
+fn calculate_common_deploy_hash(
+    transaction: &DeployTransaction,
+    chain_id: &ChainId,
+    is_deprecated: bool,
+) -> Result<TransactionHash, StarknetApiError> {
+    let contract_address = calculate_contract_address(
+        transaction.contract_address_salt,
+        transaction.class_hash,
+        &transaction.constructor_calldata,
+        ContractAddress::from(0_u8),
+    )?;
+
+    let mut hash_chain = HashChain::new().chain(&DEPLOY);
+    if !is_deprecated {
+        hash_chain = hash_chain.chain(&transaction.version.0);
+    }
+    hash_chain = hash_chain
+        .chain(contract_address.0.key())
+        .chain(&CONSTRUCTOR_ENTRY_POINT_SELECTOR.0)
+        .chain(
+            &HashChain::new()
+                .chain_iter(transaction.constructor_calldata.0.iter())
+                .get_pedersen_hash(),
+        );
+    if !is_deprecated {
+        // No fee in a deploy transaction.
+        hash_chain = hash_chain.chain(&StarkFelt::ZERO);
+    }
+    Ok(TransactionHash(hash_chain.chain(&ascii_as_felt(&chain_id.0)?).get_pedersen_hash()))
+}
+
+fn calculate_common_invoke_v0_hash(
+    transaction: &InvokeTransactionV0,
+    chain_id: &ChainId,
+    is_deprecated: bool,
+) -> Result<TransactionHash, StarknetApiError> {
+    let mut hash_chain = HashChain::new().chain(&INVOKE);
+    if !is_deprecated {
+        hash_chain = hash_chain.chain(&StarkFelt::ZERO);
+    }
+    hash_chain = hash_chain
+        .chain(transaction.contract_address.0.key())
+        .chain(&transaction.entry_point_selector.0)
+        .chain(&HashChain::new().chain_iter(transaction.calldata.0.iter()).get_pedersen_hash());
+    if !is_deprecated {
+        hash_chain = hash_chain.chain(&transaction.max_fee.into());
+    }
+    Ok(TransactionHash(hash_chain.chain(&ascii_as_felt(&chain_id.0)?).get_pedersen_hash()))
+}
+
+fn calculate_invoke_v1_hash(
+    transaction: &InvokeTransactionV1,
+    chain_id: &ChainId,
+) -> Result<TransactionHash, StarknetApiError> {
+    Ok(TransactionHash(
+        HashChain::new()
+            .chain(&INVOKE)
+            .chain(&StarkFelt::ONE)
+            .chain(transaction.sender_address.0.key())
+            // No entry point selector in an invoke V1 transaction.
+            .chain(&StarkFelt::ZERO)
+            .chain(&HashChain::new().chain_iter(transaction.calldata.0.iter()).get_pedersen_hash())
+            .chain(&transaction.max_fee.into())
+            .chain(&ascii_as_felt(&chain_id.0)?)
+            .chain(&transaction.nonce.0)
+            .get_pedersen_hash(),
+    ))
+}
+
+fn calculate_common_l1_handler_hash(
+    transaction: &L1HandlerTransaction,
+    chain_id: &ChainId,
+    version: L1HandlerVersion,
+) -> Result<TransactionHash, StarknetApiError> {
+    let is_deprecated = version != L1HandlerVersion::V0;
+    let prefix = if version == L1HandlerVersion::AsInvoke { &INVOKE } else { &L1_HANDLER };
+
+    let mut hash_chain = HashChain::new().chain(prefix);
+    if !is_deprecated {
+        hash_chain = hash_chain.chain(&transaction.version.0);
+    }
+    hash_chain = hash_chain
+        .chain(transaction.contract_address.0.key())
+        .chain(&transaction.entry_point_selector.0)
+        .chain(&HashChain::new().chain_iter(transaction.calldata.0.iter()).get_pedersen_hash());
+    if !is_deprecated {
+        // No fee in an L1 handler transaction.
+        hash_chain = hash_chain.chain(&StarkFelt::ZERO);
+    }
+    hash_chain = hash_chain.chain(&ascii_as_felt(&chain_id.0)?);
+    if !is_deprecated {
+        hash_chain = hash_chain.chain(&transaction.nonce.0);
+    }
+    Ok(TransactionHash(hash_chain.get_pedersen_hash()))
+}
+
+fn calculate_declare_v0_hash(
+    transaction: &DeclareTransactionV0V1,
+    chain_id: &ChainId,
+    version: &TransactionVersion,
+) -> Result<TransactionHash, StarknetApiError> {
+    Ok(TransactionHash(
+        HashChain::new()
+            .chain(&DECLARE)
+            .chain(&version.0)
+            .chain(transaction.sender_address.0.key())
+            // No entry point selector in a declare transaction.
+            .chain(&StarkFelt::ZERO)
+            // A declare V0 transaction commits to an empty calldata.
+            .chain(&HashChain::new().get_pedersen_hash())
+            .chain(&transaction.max_fee.into())
+            .chain(&ascii_as_felt(&chain_id.0)?)
+            .chain(&transaction.class_hash.0)
+            .get_pedersen_hash(),
+    ))
+}
+
+fn calculate_declare_v1_hash(
+    transaction: &DeclareTransactionV0V1,
+    chain_id: &ChainId,
+    version: &TransactionVersion,
+) -> Result<TransactionHash, StarknetApiError> {
+    Ok(TransactionHash(
+        HashChain::new()
+            .chain(&DECLARE)
+            .chain(&version.0)
+            .chain(transaction.sender_address.0.key())
+            // No entry point selector in a declare transaction.
+            .chain(&StarkFelt::ZERO)
+            .chain(&HashChain::new().chain(&transaction.class_hash.0).get_pedersen_hash())
+            .chain(&transaction.max_fee.into())
+            .chain(&ascii_as_felt(&chain_id.0)?)
+            .chain(&transaction.nonce.0)
+            .get_pedersen_hash(),
+    ))
+}
+
+fn calculate_declare_v2_hash(
+    transaction: &DeclareTransactionV2,
+    chain_id: &ChainId,
+    version: &TransactionVersion,
+) -> Result<TransactionHash, StarknetApiError> {
+    Ok(TransactionHash(
+        HashChain::new()
+            .chain(&DECLARE)
+            .chain(&version.0)
+            .chain(transaction.sender_address.0.key())
+            // No entry point selector in a declare transaction.
+            .chain(&StarkFelt::ZERO)
+            .chain(&HashChain::new().chain(&transaction.class_hash.0).get_pedersen_hash())
+            .chain(&transaction.max_fee.into())
+            .chain(&ascii_as_felt(&chain_id.0)?)
+            .chain(&transaction.nonce.0)
+            .chain(&transaction.compiled_class_hash.0)
+            .get_pedersen_hash(),
+    ))
+}
+
+fn calculate_deploy_account_v1_hash(
+    transaction: &DeployAccountTransactionV1,
+    chain_id: &ChainId,
+) -> Result<TransactionHash, StarknetApiError> {
+    let contract_address = calculate_contract_address(
+        transaction.contract_address_salt,
+        transaction.class_hash,
+        &transaction.constructor_calldata,
+        ContractAddress::from(0_u8),
+    )?;
+    let calldata_hash = HashChain::new()
+        .chain(&transaction.class_hash.0)
+        .chain(&transaction.contract_address_salt.0)
+        .chain_iter(transaction.constructor_calldata.0.iter())
+        .get_pedersen_hash();
+
+    Ok(TransactionHash(
+        HashChain::new()
+            .chain(&DEPLOY_ACCOUNT)
+            .chain(&StarkFelt::ONE)
+            .chain(contract_address.0.key())
+            // No entry point selector in a deploy account transaction.
+            .chain(&StarkFelt::ZERO)
+            .chain(&calldata_hash)
+            .chain(&transaction.max_fee.into())
+            .chain(&ascii_as_felt(&chain_id.0)?)
+            .chain(&transaction.nonce.0)
+            .get_pedersen_hash(),
+    ))
+}
+
+fn calculate_invoke_v3_hash(
+    transaction: &InvokeTransactionV3,
+    chain_id: &ChainId,
+) -> Result<TransactionHash, StarknetApiError> {
+    Ok(TransactionHash(
+        HashChain::new()
+            .chain(&INVOKE)
+            .chain(&StarkFelt::from(3_u8))
+            .chain(transaction.sender_address.0.key())
+            .chain(&calculate_tip_resource_bounds_hash(
+                &transaction.resource_bounds,
+                &transaction.tip,
+            ))
+            .chain(
+                &HashChain::new()
+                    .chain_iter(transaction.paymaster_data.0.iter())
+                    .get_poseidon_hash(),
+            )
+            .chain(&ascii_as_felt(&chain_id.0)?)
+            .chain(&transaction.nonce.0)
+            .chain(&concat_data_availability_modes(
+                transaction.nonce_data_availability_mode,
+                transaction.fee_data_availability_mode,
+            ))
+            .chain(
+                &HashChain::new()
+                    .chain_iter(transaction.account_deployment_data.0.iter())
+                    .get_poseidon_hash(),
+            )
+            .chain(&HashChain::new().chain_iter(transaction.calldata.0.iter()).get_poseidon_hash())
+            .get_poseidon_hash(),
+    ))
+}
+
+fn calculate_declare_v3_hash(
+    transaction: &DeclareTransactionV3,
+    chain_id: &ChainId,
+    version: &TransactionVersion,
+) -> Result<TransactionHash, StarknetApiError> {
+    Ok(TransactionHash(
+        HashChain::new()
+            .chain(&DECLARE)
+            .chain(&version.0)
+            .chain(transaction.sender_address.0.key())
+            .chain(&calculate_tip_resource_bounds_hash(
+                &transaction.resource_bounds,
+                &transaction.tip,
+            ))
+            .chain(
+                &HashChain::new()
+                    .chain_iter(transaction.paymaster_data.0.iter())
+                    .get_poseidon_hash(),
+            )
+            .chain(&ascii_as_felt(&chain_id.0)?)
+            .chain(&transaction.nonce.0)
+            .chain(&concat_data_availability_modes(
+                transaction.nonce_data_availability_mode,
+                transaction.fee_data_availability_mode,
+            ))
+            .chain(
+                &HashChain::new()
+                    .chain_iter(transaction.account_deployment_data.0.iter())
+                    .get_poseidon_hash(),
+            )
+            .chain(&transaction.class_hash.0)
+            .chain(&transaction.compiled_class_hash.0)
+            .get_poseidon_hash(),
+    ))
+}
+
+fn calculate_deploy_account_v3_hash(
+    transaction: &DeployAccountTransactionV3,
+    chain_id: &ChainId,
+) -> Result<TransactionHash, StarknetApiError> {
+    let contract_address = calculate_contract_address(
+        transaction.contract_address_salt,
+        transaction.class_hash,
+        &transaction.constructor_calldata,
+        ContractAddress::from(0_u8),
+    )?;
+
+    Ok(TransactionHash(
+        HashChain::new()
+            .chain(&DEPLOY_ACCOUNT)
+            .chain(&StarkFelt::from(3_u8))
+            .chain(contract_address.0.key())
+            .chain(&calculate_tip_resource_bounds_hash(
+                &transaction.resource_bounds,
+                &transaction.tip,
+            ))
+            .chain(
+                &HashChain::new()
+                    .chain_iter(transaction.paymaster_data.0.iter())
+                    .get_poseidon_hash(),
+            )
+            .chain(&ascii_as_felt(&chain_id.0)?)
+            .chain(&transaction.nonce.0)
+            .chain(&concat_data_availability_modes(
+                transaction.nonce_data_availability_mode,
+                transaction.fee_data_availability_mode,
+            ))
+            .chain(
+                &HashChain::new()
+                    .chain_iter(transaction.constructor_calldata.0.iter())
+                    .get_poseidon_hash(),
+            )
+            .chain(&transaction.class_hash.0)
+            .chain(&transaction.contract_address_salt.0)
+            .get_poseidon_hash(),
+    ))
+}
+
+// Returns the Poseidon hash of the tip followed by the L1 gas and L2 gas resource bounds. A missing
+// resource is bounded by zero.
+fn calculate_tip_resource_bounds_hash(
+    resource_bounds: &ResourceBoundsMapping,
+    tip: &Tip,
+) -> StarkFelt {
+    let bounds_of = |resource| resource_bounds.0.get(&resource).copied().unwrap_or_default();
+    HashChain::new()
+        .chain(&(*tip).into())
+        .chain(&concat_resource(&bounds_of(Resource::L1Gas), L1_GAS))
+        .chain(&concat_resource(&bounds_of(Resource::L2Gas), L2_GAS))
+        .get_poseidon_hash()
+}
+
+// Returns [0 | resource_name (56 bits) | max_amount (64 bits) | max_price_per_unit (128 bits)].
+fn concat_resource(resource_bounds: &ResourceBounds, resource_name: &[u8; 7]) -> StarkFelt {
+    let mut bytes = [0u8; 32];
+    bytes[1..8].copy_from_slice(resource_name);
+    bytes[8..16].copy_from_slice(&resource_bounds.max_amount.to_be_bytes());
+    bytes[16..32].copy_from_slice(&resource_bounds.max_price_per_unit.to_be_bytes());
+    StarkFelt::new(bytes).expect("A value with a zero most significant byte is a valid StarkFelt.")
+}
+
+// Returns [0 ... 0 (192 bits) | nonce_mode (32 bits) | fee_mode (32 bits)].
+fn concat_data_availability_modes(
+    nonce_mode: DataAvailabilityMode,
+    fee_mode: DataAvailabilityMode,
+) -> StarkFelt {
+    StarkFelt::from(((nonce_mode as u64) << DATA_AVAILABILITY_MODE_BITS) + fee_mode as u64)
+}
@@ -0,0 +1,496 @@
+use std::collections::BTreeMap;
+
+use crate::block::BlockNumber;
+use crate::core::{
+    CONSTRUCTOR_ENTRY_POINT_SELECTOR, ChainId, ClassHash, CompiledClassHash, ContractAddress,
+    EntryPointSelector, Nonce, PatriciaKey, calculate_contract_address,
+};
+use crate::data_availability::DataAvailabilityMode;
+use crate::hash::{StarkFelt, StarkHash, ascii_as_felt, pedersen_hash_array, poseidon_hash_many};
+use crate::transaction::{
+    AccountDeploymentData, Calldata, ContractAddressSalt, DeclareTransaction,
+    DeclareTransactionV0V1, DeclareTransactionV2, DeclareTransactionV3, DeployAccountTransaction,
+    DeployAccountTransactionV1, DeployAccountTransactionV3, DeployTransaction, Fee,
+    InvokeTransaction, InvokeTransactionV0, InvokeTransactionV1, InvokeTransactionV3,
+    L1HandlerTransaction, PaymasterData, Resource, ResourceBounds, ResourceBoundsMapping, Tip,
+    Transaction, TransactionHash, TransactionSignature, TransactionVersion,
+};
+use crate::transaction_hash::{
+    L1_GAS, MAINNET_TRANSACTION_HASH_WITH_VERSION, calculate_deprecated_transaction_hashes,
+    calculate_transaction_hash, concat_resource, validate_transaction_hash,
+};
+use crate::{calldata, class_hash, contract_address, patricia_key, stark_felt};
+
+fn chain_id() -> ChainId {
+    ChainId("SN_GOERLI".to_string())
+}
+
+#[test]
+fn invoke_v1_transaction_hash() {
+    let transaction = InvokeTransactionV1 {
+        max_fee: Fee(100),
+        signature: TransactionSignature(vec![stark_felt!("0x7")]),
+        nonce: Nonce(stark_felt!("0x3")),
+        sender_address: contract_address!("0x1234"),
+        calldata: calldata![stark_felt!("0x1"), stark_felt!("0x2")],
+    };
+
+    let expected = pedersen_hash_array(&[
+        ascii_as_felt("invoke").unwrap(),
+        stark_felt!("0x1"),
+        stark_felt!("0x1234"),
+        stark_felt!("0x0"),
+        pedersen_hash_array(&[stark_felt!("0x1"), stark_felt!("0x2")]),
+        stark_felt!("0x64"),
+        ascii_as_felt("SN_GOERLI").unwrap(),
+        stark_felt!("0x3"),
+    ]);
+    let transaction = Transaction::Invoke(InvokeTransaction::V1(transaction));
+    assert_eq!(
+        calculate_transaction_hash(&transaction, &chain_id()).unwrap(),
+        TransactionHash(expected)
+    );
+    assert!(calculate_deprecated_transaction_hashes(&transaction, &chain_id()).unwrap().is_empty());
+}
+
+#[test]
+fn invoke_v3_transaction_hash() {
+    let l1_gas_bounds = ResourceBounds { max_amount: 0x186a0, max_price_per_unit: 0x5af3107a4000 };
+    let transaction = InvokeTransactionV3 {
+        resource_bounds: ResourceBoundsMapping(BTreeMap::from([(Resource::L1Gas, l1_gas_bounds)])),
+        tip: Tip(0),
+        signature: TransactionSignature::default(),
+        nonce: Nonce(stark_felt!("0x8")),
+        sender_address: contract_address!("0x1234"),
+        calldata: calldata![stark_felt!("0x1"), stark_felt!("0x2")],
+        nonce_data_availability_mode: DataAvailabilityMode::L1,
+        fee_data_availability_mode: DataAvailabilityMode::L2,
+        paymaster_data: PaymasterData::default(),
+        account_deployment_data: AccountDeploymentData::default(),
+    };
+
+    // A missing resource is bounded by zero.
+    let tip_resource_bounds_hash = poseidon_hash_many(&[
+        stark_felt!("0x0"),
+        stark_felt!("0x00004c315f47415300000000000186a0000000000000000000005af3107a4000"),
+        stark_felt!("0x00004c325f474153000000000000000000000000000000000000000000000000"),
+    ]);
+    let expected = poseidon_hash_many(&[
+        ascii_as_felt("invoke").unwrap(),
+        stark_felt!("0x3"),
+        stark_felt!("0x1234"),
+        tip_resource_bounds_hash,
+        poseidon_hash_many(&[]),
+        ascii_as_felt("SN_GOERLI").unwrap(),
+        stark_felt!("0x8"),
+        stark_felt!("0x1"),
+        poseidon_hash_many(&[]),
+        poseidon_hash_many(&[stark_felt!("0x1"), stark_felt!("0x2")]),
+    ]);
+    assert_eq!(
+        calculate_transaction_hash(&Transaction::Invoke(transaction.into()), &chain_id()).unwrap(),
+        TransactionHash(expected)
+    );
+}
+
+#[test]
+fn resource_bounds_concatenation() {
+    let bounds = ResourceBounds { max_amount: u64::MAX, max_price_per_unit: u128::MAX };
+    assert_eq!(
+        concat_resource(&bounds, L1_GAS),
+        stark_felt!("0x00004c315f474153ffffffffffffffffffffffffffffffffffffffffffffffff")
+    );
+}
+
+#[test]
+fn invoke_v0_transaction_hashes() {
+    let transaction = Transaction::Invoke(InvokeTransaction::V0(InvokeTransactionV0 {
+        max_fee: Fee(3),
+        signature: TransactionSignature::default(),
+        contract_address: contract_address!("0x77"),
+        entry_point_selector: EntryPointSelector(stark_felt!("0x88")),
+        calldata: calldata![stark_felt!("0x99")],
+    }));
+
+    let expected = pedersen_hash_array(&[
+        ascii_as_felt("invoke").unwrap(),
+        stark_felt!("0x0"),
+        stark_felt!("0x77"),
+        stark_felt!("0x88"),
+        pedersen_hash_array(&[stark_felt!("0x99")]),
+        stark_felt!("0x3"),
+        ascii_as_felt("SN_GOERLI").unwrap(),
+    ]);
+    assert_eq!(
+        calculate_transaction_hash(&transaction, &chain_id()).unwrap(),
+        TransactionHash(expected)
+    );
+
+    // The deprecated hash has neither a version nor a fee.
+    let expected_deprecated = pedersen_hash_array(&[
+        ascii_as_felt("invoke").unwrap(),
+        stark_felt!("0x77"),
+        stark_felt!("0x88"),
+        pedersen_hash_array(&[stark_felt!("0x99")]),
+        ascii_as_felt("SN_GOERLI").unwrap(),
+    ]);
+    assert_eq!(
+        calculate_deprecated_transaction_hashes(&transaction, &chain_id()).unwrap(),
+        vec![TransactionHash(expected_deprecated)]
+    );
+}
+
+#[test]
+fn declare_v0_v1_transaction_hashes() {
+    let transaction = DeclareTransactionV0V1 {
+        max_fee: Fee(1),
+        signature: TransactionSignature::default(),
+        nonce: Nonce(stark_felt!("0x2")),
+        class_hash: class_hash!("0xabc"),
+        sender_address: contract_address!("0x5"),
+    };
+
+    // A declare V0 transaction commits to an empty calldata and to its class hash instead of its
+    // nonce.
+    let expected_v0 = pedersen_hash_array(&[
+        ascii_as_felt("declare").unwrap(),
+        stark_felt!("0x0"),
+        stark_felt!("0x5"),
+        stark_felt!("0x0"),
+        pedersen_hash_array(&[]),
+        stark_felt!("0x1"),
+        ascii_as_felt("SN_GOERLI").unwrap(),
+        stark_felt!("0xabc"),
+    ]);
+    let expected_v1 = pedersen_hash_array(&[
+        ascii_as_felt("declare").unwrap(),
+        stark_felt!("0x1"),
+        stark_felt!("0x5"),
+        stark_felt!("0x0"),
+        pedersen_hash_array(&[stark_felt!("0xabc")]),
+        stark_felt!("0x1"),
+        ascii_as_felt("SN_GOERLI").unwrap(),
+        stark_felt!("0x2"),
+    ]);
+    for (transaction, expected) in [
+        (DeclareTransaction::V0(transaction.clone()), expected_v0),
+        (DeclareTransaction::V1(transaction), expected_v1),
+    ] {
+        let transaction = Transaction::Declare(transaction);
+        assert_eq!(
+            calculate_transaction_hash(&transaction, &chain_id()).unwrap(),
+            TransactionHash(expected)
+        );
+        assert!(
+            calculate_deprecated_transaction_hashes(&transaction, &chain_id()).unwrap().is_empty()
+        );
+    }
+}
+
+#[test]
+fn declare_v2_transaction_hash() {
+    let transaction = DeclareTransactionV2 {
+        max_fee: Fee(1),
+        signature: TransactionSignature::default(),
+        nonce: Nonce(stark_felt!("0x2")),
+        class_hash: class_hash!("0xabc"),
+        compiled_class_hash: CompiledClassHash(stark_felt!("0xdef")),
+        sender_address: contract_address!("0x5"),
+    };
+
+    let expected = pedersen_hash_array(&[
+        ascii_as_felt("declare").unwrap(),
+        stark_felt!("0x2"),
+        stark_felt!("0x5"),
+        stark_felt!("0x0"),
+        pedersen_hash_array(&[stark_felt!("0xabc")]),
+        stark_felt!("0x1"),
+        ascii_as_felt("SN_GOERLI").unwrap(),
+        stark_felt!("0x2"),
+        stark_felt!("0xdef"),
+    ]);
+    let transaction = Transaction::Declare(DeclareTransaction::V2(transaction));
+    assert_eq!(
+        calculate_transaction_hash(&transaction, &chain_id()).unwrap(),
+        TransactionHash(expected)
+    );
+}
+
+#[test]
+fn deploy_account_transaction_hash() {
+    let transaction = DeployAccountTransactionV1 {
+        max_fee: Fee(2),
+        signature: TransactionSignature::default(),
+        nonce: Nonce::default(),
+        class_hash: class_hash!("0x10"),
+        contract_address_salt: ContractAddressSalt(stark_felt!("0x20")),
+        constructor_calldata: calldata![stark_felt!("0x30")],
+    };
+    let contract_address = calculate_contract_address(
+        transaction.contract_address_salt,
+        transaction.class_hash,
+        &transaction.constructor_calldata,
+        ContractAddress::default(),
+    )
+    .unwrap();
+
+    let expected = pedersen_hash_array(&[
+        ascii_as_felt("deploy_account").unwrap(),
+        stark_felt!("0x1"),
+        *contract_address.0.key(),
+        stark_felt!("0x0"),
+        pedersen_hash_array(&[stark_felt!("0x10"), stark_felt!("0x20"), stark_felt!("0x30")]),
+        stark_felt!("0x2"),
+        ascii_as_felt("SN_GOERLI").unwrap(),
+        stark_felt!("0x0"),
+    ]);
+    assert_eq!(
+        calculate_transaction_hash(
+            &Transaction::DeployAccount(DeployAccountTransaction::V1(transaction)),
+            &chain_id()
+        )
+        .unwrap(),
+        TransactionHash(expected)
+    );
+}
+
+#[test]
+fn declare_v3_transaction_hash() {
+    let l2_gas_bounds = ResourceBounds { max_amount: 0x10, max_price_per_unit: 0x20 };
+    let transaction = DeclareTransactionV3 {
+        resource_bounds: ResourceBoundsMapping(BTreeMap::from([(Resource::L2Gas, l2_gas_bounds)])),
+        tip: Tip(0x5),
+        signature: TransactionSignature(vec![stark_felt!("0x7")]),
+        nonce: Nonce(stark_felt!("0x2")),
+        class_hash: class_hash!("0xabc"),
+        compiled_class_hash: CompiledClassHash(stark_felt!("0xdef")),
+        sender_address: contract_address!("0x5"),
+        nonce_data_availability_mode: DataAvailabilityMode::L2,
+        fee_data_availability_mode: DataAvailabilityMode::L1,
+        paymaster_data: PaymasterData(vec![stark_felt!("0x11")]),
+        account_deployment_data: AccountDeploymentData(vec![stark_felt!("0x22")]),
+    };
+
+    let tip_resource_bounds_hash = poseidon_hash_many(&[
+        stark_felt!("0x5"),
+        stark_felt!("0x00004c315f474153000000000000000000000000000000000000000000000000"),
+        stark_felt!("0x00004c325f474153000000000000001000000000000000000000000000000020"),
+    ]);
+    let expected = poseidon_hash_many(&[
+        ascii_as_felt("declare").unwrap(),
+        stark_felt!("0x3"),
+        stark_felt!("0x5"),
+        tip_resource_bounds_hash,
+        poseidon_hash_many(&[stark_felt!("0x11")]),
+        ascii_as_felt("SN_GOERLI").unwrap(),
+        stark_felt!("0x2"),
+        stark_felt!("0x100000000"),
+        poseidon_hash_many(&[stark_felt!("0x22")]),
+        stark_felt!("0xabc"),
+        stark_felt!("0xdef"),
+    ]);
+    let transaction = Transaction::Declare(DeclareTransaction::V3(transaction));
+    assert_eq!(
+        calculate_transaction_hash(&transaction, &chain_id()).unwrap(),
+        TransactionHash(expected)
+    );
+    assert!(calculate_deprecated_transaction_hashes(&transaction, &chain_id()).unwrap().is_empty());
+}
+
+#[test]
+fn deploy_account_v3_transaction_hash() {
+    let l1_gas_bounds = ResourceBounds { max_amount: 0x186a0, max_price_per_unit: 0x5af3107a4000 };
+    let transaction = DeployAccountTransactionV3 {
+        resource_bounds: ResourceBoundsMapping(BTreeMap::from([(Resource::L1Gas, l1_gas_bounds)])),
+        tip: Tip(0),
+        signature: TransactionSignature::default(),
+        nonce: Nonce::default(),
+        class_hash: class_hash!("0x10"),
+        contract_address_salt: ContractAddressSalt(stark_felt!("0x20")),
+        constructor_calldata: calldata![stark_felt!("0x30"), stark_felt!("0x40")],
+        nonce_data_availability_mode: DataAvailabilityMode::L1,
+        fee_data_availability_mode: DataAvailabilityMode::L1,
+        paymaster_data: PaymasterData::default(),
+    };
+    let contract_address = calculate_contract_address(
+        transaction.contract_address_salt,
+        transaction.class_hash,
+        &transaction.constructor_calldata,
+        ContractAddress::default(),
+    )
+    .unwrap();
+
+    let tip_resource_bounds_hash = poseidon_hash_many(&[
+        stark_felt!("0x0"),
+        stark_felt!("0x00004c315f47415300000000000186a0000000000000000000005af3107a4000"),
+        stark_felt!("0x00004c325f474153000000000000000000000000000000000000000000000000"),
+    ]);
+    let expected = poseidon_hash_many(&[
+        ascii_as_felt("deploy_account").unwrap(),
+        stark_felt!("0x3"),
+        *contract_address.0.key(),
+        tip_resource_bounds_hash,
+        poseidon_hash_many(&[]),
+        ascii_as_felt("SN_GOERLI").unwrap(),
+        stark_felt!("0x0"),
+        stark_felt!("0x0"),
+        poseidon_hash_many(&[stark_felt!("0x30"), stark_felt!("0x40")]),
+        stark_felt!("0x10"),
+        stark_felt!("0x20"),
+    ]);
+    let transaction = Transaction::DeployAccount(DeployAccountTransaction::V3(transaction));
+    assert_eq!(
+        calculate_transaction_hash(&transaction, &chain_id()).unwrap(),
+        TransactionHash(expected)
+    );
+    assert!(calculate_deprecated_transaction_hashes(&transaction, &chain_id()).unwrap().is_empty());
+}
+
+#[test]
+fn deploy_transaction_hashes() {
+    let transaction = DeployTransaction {
+        version: TransactionVersion(stark_felt!("0x0")),
+        class_hash: class_hash!("0x10"),
+        contract_address_salt: ContractAddressSalt(stark_felt!("0x20")),
+        constructor_calldata: calldata![stark_felt!("0x30")],
+    };
+    let contract_address = calculate_contract_address(
+        transaction.contract_address_salt,
+        transaction.class_hash,
+        &transaction.constructor_calldata,
+        ContractAddress::default(),
+    )
+    .unwrap();
+    let transaction = Transaction::Deploy(transaction);
+
+    let expected = pedersen_hash_array(&[
+        ascii_as_felt("deploy").unwrap(),
+        stark_felt!("0x0"),
+        *contract_address.0.key(),
+        CONSTRUCTOR_ENTRY_POINT_SELECTOR.0,
+        pedersen_hash_array(&[stark_felt!("0x30")]),
+        stark_felt!("0x0"),
+        ascii_as_felt("SN_GOERLI").unwrap(),
+    ]);
+    assert_eq!(
+        calculate_transaction_hash(&transaction, &chain_id()).unwrap(),
+        TransactionHash(expected)
+    );
+
+    // The deprecated hash has neither a version nor a fee.
+    let expected_deprecated = pedersen_hash_array(&[
+        ascii_as_felt("deploy").unwrap(),
+        *contract_address.0.key(),
+        CONSTRUCTOR_ENTRY_POINT_SELECTOR.0,
+        pedersen_hash_array(&[stark_felt!("0x30")]),
+        ascii_as_felt("SN_GOERLI").unwrap(),
+    ]);
+    assert_eq!(
+        calculate_deprecated_transaction_hashes(&transaction, &chain_id()).unwrap(),
+        vec![TransactionHash(expected_deprecated)]
+    );
+}
+
+#[test]
+fn l1_handler_transaction_hashes() {
+    let transaction = L1HandlerTransaction {
+        version: TransactionVersion(stark_felt!("0x0")),
+        nonce: Nonce(stark_felt!("0x9")),
+        contract_address: contract_address!("0x77"),
+        entry_point_selector: EntryPointSelector(stark_felt!("0x88")),
+        calldata: calldata![stark_felt!("0x99")],
+    };
+    let transaction = Transaction::L1Handler(transaction);
+
+    let expected = pedersen_hash_array(&[
+        ascii_as_felt("l1_handler").unwrap(),
+        stark_felt!("0x0"),
+        stark_felt!("0x77"),
+        stark_felt!("0x88"),
+        pedersen_hash_array(&[stark_felt!("0x99")]),
+        stark_felt!("0x0"),
+        ascii_as_felt("SN_GOERLI").unwrap(),
+        stark_felt!("0x9"),
+    ]);
+    assert_eq!(
+        calculate_transaction_hash(&transaction, &chain_id()).unwrap(),
+        TransactionHash(expected)
+    );
+
+    let expected_as_invoke = pedersen_hash_array(&[
+        ascii_as_felt("invoke").unwrap(),
+        stark_felt!("0x77"),
+        stark_felt!("0x88"),
+        pedersen_hash_array(&[stark_felt!("0x99")]),
+        ascii_as_felt("SN_GOERLI").unwrap(),
+    ]);
+    let expected_v0_deprecated = pedersen_hash_array(&[
+        ascii_as_felt("l1_handler").unwrap(),
+        stark_felt!("0x77"),
+        stark_felt!("0x88"),
+        pedersen_hash_array(&[stark_felt!("0x99")]),
+        ascii_as_felt("SN_GOERLI").unwrap(),
+    ]);
+    assert_eq!(
+        calculate_deprecated_transaction_hashes(&transaction, &chain_id()).unwrap(),
+        vec![TransactionHash(expected_as_invoke), TransactionHash(expected_v0_deprecated)]
+    );
+}
+
+#[test]
+fn validate_deprecated_transaction_hashes() {
+    let invoke = Transaction::Invoke(InvokeTransaction::V0(InvokeTransactionV0 {
+        max_fee: Fee(0),
+        signature: TransactionSignature::default(),
+        contract_address: contract_address!("0x77"),
+        entry_point_selector: EntryPointSelector(stark_felt!("0x88")),
+        calldata: calldata![],
+    }));
+    let deploy = Transaction::Deploy(DeployTransaction {
+        version: TransactionVersion(stark_felt!("0x0")),
+        class_hash: class_hash!("0x10"),
+        contract_address_salt: ContractAddressSalt(stark_felt!("0x20")),
+        constructor_calldata: calldata![],
+    });
+    let l1_handler = Transaction::L1Handler(L1HandlerTransaction {
+        version: TransactionVersion(stark_felt!("0x0")),
+        nonce: Nonce(stark_felt!("0x9")),
+        contract_address: contract_address!("0x77"),
+        entry_point_selector: EntryPointSelector(stark_felt!("0x88")),
+        calldata: calldata![],
+    });
+    let mainnet = ChainId("SN_MAIN".to_string());
+    let old_block = BlockNumber(1);
+    let new_block = MAINNET_TRANSACTION_HASH_WITH_VERSION.next();
+
+    for transaction in [invoke, deploy, l1_handler] {
+        let hash = calculate_transaction_hash(&transaction, &mainnet).unwrap();
+        assert!(validate_transaction_hash(&transaction, &old_block, &mainnet, hash).unwrap());
+        assert!(validate_transaction_hash(&transaction, &new_block, &mainnet, hash).unwrap());
+
+        let deprecated_hashes =
+            calculate_deprecated_transaction_hashes(&transaction, &mainnet).unwrap();
+        assert!(!deprecated_hashes.is_empty());
+        for deprecated_hash in deprecated_hashes {
+            assert_ne!(deprecated_hash, hash);
+            assert!(
+                validate_transaction_hash(&transaction, &old_block, &mainnet, deprecated_hash)
+                    .unwrap()
+            );
+            assert!(
+                !validate_transaction_hash(&transaction, &new_block, &mainnet, deprecated_hash)
+                    .unwrap()
+            );
+        }
+
+        // Deprecated hashes are accepted in any block of other chains.
+        for deprecated_hash in
+            calculate_deprecated_transaction_hashes(&transaction, &chain_id()).unwrap()
+        {
+            assert!(
+                validate_transaction_hash(&transaction, &new_block, &chain_id(), deprecated_hash)
+                    .unwrap()
+            );
+        }
+    }
+}
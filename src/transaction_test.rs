@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+
+use assert_matches::assert_matches;
+
+use crate::compiled_class::SierraVersion;
+use crate::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce, PatriciaKey};
+use crate::data_availability::DataAvailabilityMode;
+use crate::hash::{StarkFelt, StarkHash};
+use crate::state::ContractClass;
+use crate::test_utils::{get_compile_options, get_minimal_contract_class};
+use crate::transaction::{
+    AccountDeploymentData, Calldata, ContractAddressSalt, DeclareTransaction, DeclareTransactionV2,
+    DeclareTransactionV3, DeployAccountTransaction, DeployAccountTransactionV1,
+    DeployAccountTransactionV3, Fee, InvokeTransaction, InvokeTransactionV3, PaymasterData,
+    Resource, ResourceBounds, ResourceBoundsMapping, RevertedTransactionExecutionStatus, Tip,
+    Transaction, TransactionExecutionStatus, TransactionSignature,
+};
+use crate::{StarknetApiError, calldata, class_hash, contract_address, patricia_key, stark_felt};
+
+// The minimal contract of the test data of the Cairo compiler, and a transaction that declares it.
+fn get_declared_class() -> (ContractClass, DeclareTransactionV2) {
+    let contract_class = get_minimal_contract_class();
+    let tx = DeclareTransactionV2 {
+        // The hash of the class by a separate implementation of the Sierra class hash.
+        class_hash: class_hash!("0xdd294770e647d4c34f1953c5b5f19e9644a8413bfbc46f5981d9b99ceb6e32"),
+        // The hash that the Cairo compiler gives the compiled class in its test data.
+        compiled_class_hash: CompiledClassHash(stark_felt!(
+            "0x5fd87fec0614148ce800defb1ad3dbb9212451672e244d24fb1aa359e342055"
+        )),
+        ..Default::default()
+    };
+    (contract_class, tx)
+}
+
+#[test]
+fn declare_v2_verify_class() {
+    let (contract_class, tx) = get_declared_class();
+    tx.verify_class(&contract_class, get_compile_options()).unwrap();
+}
+
+#[test]
+fn declare_v2_verify_class_hash_mismatch() {
+    let (contract_class, tx) = get_declared_class();
+    let calculated = tx.class_hash;
+    let tx = DeclareTransactionV2 { class_hash: class_hash!(1_u64), ..tx };
+    assert_matches!(
+        tx.verify_class(&contract_class, get_compile_options()),
+        Err(StarknetApiError::ClassHashMismatch { expected, calculated: hash })
+        if expected == class_hash!(1_u64) && hash == calculated
+    );
+}
+
+#[test]
+fn declare_v2_verify_compiled_class_hash_mismatch() {
+    let (contract_class, tx) = get_declared_class();
+    let calculated = tx.compiled_class_hash;
+    let tx =
+        DeclareTransactionV2 { compiled_class_hash: CompiledClassHash(stark_felt!(1_u64)), ..tx };
+    assert_matches!(
+        tx.verify_class(&contract_class, get_compile_options()),
+        Err(StarknetApiError::CompiledClassHashMismatch { expected, calculated: hash })
+        if expected == CompiledClassHash(stark_felt!(1_u64)) && hash == calculated
+    );
+}
+
+#[test]
+fn declare_v2_verify_class_compilation_failure() {
+    // The class hash matches, so the class is compiled, which fails for an invalid program.
+    let contract_class = ContractClass {
+        sierra_program: vec![stark_felt!(1_u64)],
+        contract_class_version: "0.1.0".to_owned(),
+        ..ContractClass::default()
+    };
+    let tx = DeclareTransactionV2 {
+        class_hash: contract_class.class_hash().unwrap(),
+        ..Default::default()
+    };
+    assert_matches!(
+        tx.verify_class(&contract_class, get_compile_options()),
+        Err(StarknetApiError::InvalidSierraProgram(_))
+    );
+}
+
+#[test]
+fn declare_v2_verify_class_unsupported_sierra_version() {
+    let (mut contract_class, _) = get_declared_class();
+    contract_class.sierra_program[1] = stark_felt!(6_u64);
+    let tx = DeclareTransactionV2 {
+        class_hash: contract_class.class_hash().unwrap(),
+        ..Default::default()
+    };
+    assert_matches!(
+        tx.verify_class(&contract_class, get_compile_options()),
+        Err(StarknetApiError::UnsupportedSierraVersion { version, .. })
+        if version == SierraVersion { major: 1, minor: 6, patch: 0 }
+    );
+}
+
+#[test]
+fn execution_status_serde() {
+    let reverted_with_reason =
+        TransactionExecutionStatus::Reverted(RevertedTransactionExecutionStatus {
+            revert_reason: "reason".to_owned(),
+        });
+    let reverted =
+        TransactionExecutionStatus::Reverted(RevertedTransactionExecutionStatus::default());
+    for status in
+        [TransactionExecutionStatus::Succeeded, reverted_with_reason.clone(), reverted.clone()]
+    {
+        let serialized = serde_json::to_string(&status).unwrap();
+        assert_eq!(
+            serde_json::from_str::<TransactionExecutionStatus>(&serialized).unwrap(),
+            status
+        );
+    }
+
+    assert_eq!(serde_json::to_string(&reverted).unwrap(), r#""REVERTED""#);
+    assert_eq!(
+        serde_json::to_value(&reverted_with_reason).unwrap(),
+        serde_json::json!({"REVERTED": {"revert_reason": "reason"}})
+    );
+
+    // Statuses stored before the revert reason was added.
+    assert_eq!(
+        serde_json::from_str::<TransactionExecutionStatus>(r#""REVERTED""#).unwrap(),
+        reverted
+    );
+    assert_eq!(
+        serde_json::from_str::<TransactionExecutionStatus>(r#""SUCCEEDED""#).unwrap(),
+        TransactionExecutionStatus::Succeeded
+    );
+}
+
+fn get_resource_bounds() -> ResourceBoundsMapping {
+    ResourceBoundsMapping(BTreeMap::from([
+        (Resource::L1Gas, ResourceBounds { max_amount: 0x10, max_price_per_unit: 0x100 }),
+        (Resource::L2Gas, ResourceBounds { max_amount: 0x1, max_price_per_unit: 0x2 }),
+    ]))
+}
+
+#[test]
+fn v3_transactions_serde() {
+    let invoke = InvokeTransactionV3 {
+        resource_bounds: get_resource_bounds(),
+        tip: Tip(0x3),
+        signature: TransactionSignature(vec![stark_felt!("0x4")]),
+        nonce: Nonce(stark_felt!("0x5")),
+        sender_address: contract_address!("0x6"),
+        calldata: calldata![stark_felt!("0x7")],
+        nonce_data_availability_mode: DataAvailabilityMode::L1,
+        fee_data_availability_mode: DataAvailabilityMode::L2,
+        paymaster_data: PaymasterData(vec![stark_felt!("0x8")]),
+        account_deployment_data: AccountDeploymentData(vec![stark_felt!("0x9")]),
+    };
+    let declare = DeclareTransactionV3 {
+        resource_bounds: get_resource_bounds(),
+        tip: Tip(0x3),
+        signature: TransactionSignature(vec![stark_felt!("0x4")]),
+        nonce: Nonce(stark_felt!("0x5")),
+        class_hash: class_hash!("0xa"),
+        compiled_class_hash: CompiledClassHash(stark_felt!("0xb")),
+        sender_address: contract_address!("0x6"),
+        nonce_data_availability_mode: DataAvailabilityMode::L2,
+        fee_data_availability_mode: DataAvailabilityMode::L1,
+        paymaster_data: PaymasterData(vec![stark_felt!("0x8")]),
+        account_deployment_data: AccountDeploymentData(vec![stark_felt!("0x9")]),
+    };
+    let deploy_account = DeployAccountTransactionV3 {
+        resource_bounds: get_resource_bounds(),
+        tip: Tip(0x3),
+        signature: TransactionSignature(vec![stark_felt!("0x4")]),
+        nonce: Nonce(stark_felt!("0x5")),
+        class_hash: class_hash!("0xa"),
+        contract_address_salt: ContractAddressSalt(stark_felt!("0xc")),
+        constructor_calldata: calldata![stark_felt!("0x7")],
+        nonce_data_availability_mode: DataAvailabilityMode::L1,
+        fee_data_availability_mode: DataAvailabilityMode::L1,
+        paymaster_data: PaymasterData(vec![stark_felt!("0x8")]),
+    };
+
+    for transaction in [
+        Transaction::Invoke(InvokeTransaction::V3(invoke)),
+        Transaction::Declare(DeclareTransaction::V3(declare)),
+        Transaction::DeployAccount(DeployAccountTransaction::V3(deploy_account)),
+    ] {
+        let serialized = serde_json::to_string(&transaction).unwrap();
+        assert_eq!(serde_json::from_str::<Transaction>(&serialized).unwrap(), transaction);
+    }
+}
+
+#[test]
+fn deploy_account_without_version_variant_serde() {
+    let transaction = DeployAccountTransactionV1 {
+        max_fee: Fee(0x1),
+        signature: TransactionSignature(vec![stark_felt!("0x2")]),
+        nonce: Nonce(stark_felt!("0x3")),
+        class_hash: class_hash!("0x4"),
+        contract_address_salt: ContractAddressSalt(stark_felt!("0x5")),
+        constructor_calldata: calldata![stark_felt!("0x6")],
+    };
+
+    // Deploy account transactions stored before V3 are a single struct with a version.
+    let mut stored = serde_json::to_value(&transaction).unwrap();
+    stored["version"] = serde_json::to_value(stark_felt!("0x1")).unwrap();
+    assert_eq!(
+        serde_json::from_value::<DeployAccountTransaction>(stored.clone()).unwrap(),
+        DeployAccountTransaction::V1(transaction)
+    );
+
+    stored["version"] = serde_json::to_value(stark_felt!("0x0")).unwrap();
+    assert!(serde_json::from_value::<DeployAccountTransaction>(stored).is_err());
+}
+
+#[test]
+fn v3_max_fee() {
+    let resource_bounds = get_resource_bounds();
+    assert_eq!(resource_bounds.max_fee(), Fee(0x1002));
+    let transaction = DeployAccountTransaction::V3(DeployAccountTransactionV3 {
+        resource_bounds,
+        ..Default::default()
+    });
+    assert_eq!(transaction.max_fee(), Fee(0x1002));
+
+    let saturating_bounds = ResourceBoundsMapping(BTreeMap::from([
+        (Resource::L1Gas, ResourceBounds { max_amount: u64::MAX, max_price_per_unit: u128::MAX }),
+        (Resource::L2Gas, ResourceBounds { max_amount: 0x1, max_price_per_unit: 0x1 }),
+    ]));
+    assert_eq!(saturating_bounds.max_fee(), Fee(u128::MAX));
+}
@@ -0,0 +1,144 @@
+#[cfg(test)]
+#[path = "versioned_state_test.rs"]
+mod versioned_state_test;
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::StarknetApiError;
+use crate::block::BlockNumber;
+use crate::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use crate::hash::StarkFelt;
+use crate::state::{StateNumber, StorageKey, ThinStateDiff};
+
+// The values a key took, with the block that set each of them, in ascending block order.
+type ChangeList<V> = Vec<(BlockNumber, V)>;
+
+/// The state diffs of a chain from its genesis, indexed to answer reads of any state in it.
+///
+/// Every key keeps the list of the blocks that changed it, so a read is a binary search in that
+/// list. Entries that were never set read as zero.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct VersionedState {
+    state_diffs: Vec<ThinStateDiff>,
+    storage: HashMap<(ContractAddress, StorageKey), ChangeList<StarkFelt>>,
+    nonces: HashMap<ContractAddress, ChangeList<Nonce>>,
+    class_hashes: HashMap<ContractAddress, ChangeList<ClassHash>>,
+    compiled_class_hashes: HashMap<ClassHash, ChangeList<CompiledClassHash>>,
+}
+
+impl VersionedState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of the block whose state diff is appended next.
+    pub fn next_block_number(&self) -> BlockNumber {
+        BlockNumber(u64::try_from(self.state_diffs.len()).expect("Got 2^64 blocks or more."))
+    }
+
+    /// Appends the state diff of the next block.
+    pub fn append(
+        &mut self,
+        block_number: BlockNumber,
+        state_diff: ThinStateDiff,
+    ) -> Result<(), StarknetApiError> {
+        let expected = self.next_block_number();
+        if block_number != expected {
+            return Err(StarknetApiError::UnexpectedBlockNumber { expected, got: block_number });
+        }
+
+        for (address, class_hash) in
+            state_diff.deployed_contracts.iter().chain(state_diff.replaced_classes.iter())
+        {
+            record_change(&mut self.class_hashes, *address, block_number, *class_hash);
+        }
+        for (address, storage_diff) in &state_diff.storage_diffs {
+            for (key, value) in storage_diff {
+                record_change(&mut self.storage, (*address, *key), block_number, *value);
+            }
+        }
+        for (address, nonce) in &state_diff.nonces {
+            record_change(&mut self.nonces, *address, block_number, *nonce);
+        }
+        for (class_hash, compiled_class_hash) in &state_diff.declared_classes {
+            record_change(
+                &mut self.compiled_class_hashes,
+                *class_hash,
+                block_number,
+                *compiled_class_hash,
+            );
+        }
+        self.state_diffs.push(state_diff);
+        Ok(())
+    }
+
+    /// Returns the state diff of a block, or None if it was not appended.
+    pub fn state_diff(&self, block_number: BlockNumber) -> Option<&ThinStateDiff> {
+        self.state_diffs.get(usize::try_from(block_number.0).ok()?)
+    }
+
+    /// Returns the value of a storage key of a contract in the given state.
+    pub fn storage_at(
+        &self,
+        state_number: StateNumber,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> Result<StarkFelt, StarknetApiError> {
+        self.value_at(state_number, self.storage.get(&(contract_address, key)))
+    }
+
+    /// Returns the nonce of a contract in the given state.
+    pub fn nonce_at(
+        &self,
+        state_number: StateNumber,
+        contract_address: ContractAddress,
+    ) -> Result<Nonce, StarknetApiError> {
+        self.value_at(state_number, self.nonces.get(&contract_address))
+    }
+
+    /// Returns the class hash of a contract in the given state; zero if it is not deployed.
+    pub fn class_hash_at(
+        &self,
+        state_number: StateNumber,
+        contract_address: ContractAddress,
+    ) -> Result<ClassHash, StarknetApiError> {
+        self.value_at(state_number, self.class_hashes.get(&contract_address))
+    }
+
+    /// Returns the compiled class hash of a Cairo 1 class in the given state; zero if it is not
+    /// declared.
+    pub fn compiled_class_hash_at(
+        &self,
+        state_number: StateNumber,
+        class_hash: ClassHash,
+    ) -> Result<CompiledClassHash, StarknetApiError> {
+        self.value_at(state_number, self.compiled_class_hashes.get(&class_hash))
+    }
+
+    // The value in the given state is the last one set by a block before the state.
+    fn value_at<V: Copy + Default>(
+        &self,
+        state_number: StateNumber,
+        changes: Option<&ChangeList<V>>,
+    ) -> Result<V, StarknetApiError> {
+        if state_number.is_after(self.next_block_number()) {
+            return Err(StarknetApiError::UnknownState(state_number));
+        }
+        let Some(changes) = changes else {
+            return Ok(V::default());
+        };
+        let num_changes_before =
+            changes.partition_point(|(block_number, _)| state_number.is_after(*block_number));
+        Ok(num_changes_before.checked_sub(1).map_or(V::default(), |index| changes[index].1))
+    }
+}
+
+fn record_change<K: Eq + Hash, V>(
+    changes: &mut HashMap<K, ChangeList<V>>,
+    key: K,
+    block_number: BlockNumber,
+    value: V,
+) {
+    changes.entry(key).or_default().push((block_number, value));
+}
@@ -0,0 +1,102 @@
+use assert_matches::assert_matches;
+use indexmap::indexmap;
+
+use crate::block::BlockNumber;
+use crate::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce, PatriciaKey};
+use crate::hash::{StarkFelt, StarkHash};
+use crate::state::{StateNumber, StorageKey, ThinStateDiff};
+use crate::versioned_state::VersionedState;
+use crate::{StarknetApiError, class_hash, contract_address, patricia_key, stark_felt};
+
+// Block 0 deploys contract 1 and sets its storage, block 1 changes nothing and block 2 replaces
+// its class, bumps its nonce, deletes its storage and declares a class.
+fn get_versioned_state() -> VersionedState {
+    let address = contract_address!(1_u64);
+    let mut state = VersionedState::new();
+    state
+        .append(
+            BlockNumber(0),
+            ThinStateDiff {
+                deployed_contracts: indexmap! { address => class_hash!(1_u64) },
+                storage_diffs: indexmap! {
+                    address => indexmap! { StorageKey::from(1_u64) => stark_felt!(1_u64) },
+                },
+                ..ThinStateDiff::default()
+            },
+        )
+        .unwrap();
+    state.append(BlockNumber(1), ThinStateDiff::default()).unwrap();
+    state
+        .append(
+            BlockNumber(2),
+            ThinStateDiff {
+                storage_diffs: indexmap! {
+                    address => indexmap! { StorageKey::from(1_u64) => StarkFelt::ZERO },
+                },
+                declared_classes: indexmap! {
+                    class_hash!(2_u64) => CompiledClassHash(stark_felt!(3_u64)),
+                },
+                nonces: indexmap! { address => Nonce(stark_felt!(1_u64)) },
+                replaced_classes: indexmap! { address => class_hash!(2_u64) },
+                ..ThinStateDiff::default()
+            },
+        )
+        .unwrap();
+    state
+}
+
+#[test]
+fn versioned_state_reads() {
+    let state = get_versioned_state();
+    let address = contract_address!(1_u64);
+    let key = StorageKey::from(1_u64);
+
+    let before_genesis = StateNumber::right_before_block(BlockNumber(0));
+    assert_eq!(state.storage_at(before_genesis, address, key).unwrap(), StarkFelt::ZERO);
+    assert_eq!(state.class_hash_at(before_genesis, address).unwrap(), ClassHash::default());
+
+    for block_number in [BlockNumber(0), BlockNumber(1)] {
+        let state_number = StateNumber::right_after_block(block_number);
+        assert_eq!(state.storage_at(state_number, address, key).unwrap(), stark_felt!(1_u64));
+        assert_eq!(state.class_hash_at(state_number, address).unwrap(), class_hash!(1_u64));
+        assert_eq!(state.nonce_at(state_number, address).unwrap(), Nonce::default());
+        assert_eq!(
+            state.compiled_class_hash_at(state_number, class_hash!(2_u64)).unwrap(),
+            CompiledClassHash::default()
+        );
+    }
+
+    let after_last_block = StateNumber::right_after_block(BlockNumber(2));
+    assert_eq!(state.storage_at(after_last_block, address, key).unwrap(), StarkFelt::ZERO);
+    assert_eq!(state.class_hash_at(after_last_block, address).unwrap(), class_hash!(2_u64));
+    assert_eq!(state.nonce_at(after_last_block, address).unwrap(), Nonce(stark_felt!(1_u64)));
+    assert_eq!(
+        state.compiled_class_hash_at(after_last_block, class_hash!(2_u64)).unwrap(),
+        CompiledClassHash(stark_felt!(3_u64))
+    );
+}
+
+#[test]
+fn versioned_state_unknown_state() {
+    let state = get_versioned_state();
+    let state_number = StateNumber::right_after_block(BlockNumber(3));
+    assert_matches!(
+        state.nonce_at(state_number, contract_address!(1_u64)),
+        Err(StarknetApiError::UnknownState(unknown)) if unknown == state_number
+    );
+}
+
+#[test]
+fn versioned_state_append_out_of_order() {
+    let mut state = get_versioned_state();
+    assert_eq!(state.next_block_number(), BlockNumber(3));
+    assert_matches!(
+        state.append(BlockNumber(4), ThinStateDiff::default()),
+        Err(StarknetApiError::UnexpectedBlockNumber {
+            expected: BlockNumber(3),
+            got: BlockNumber(4)
+        })
+    );
+    assert_eq!(state.state_diff(BlockNumber(1)), Some(&ThinStateDiff::default()));
+    assert_eq!(state.state_diff(BlockNumber(3)), None);
+}